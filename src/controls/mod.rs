@@ -2,7 +2,11 @@ use crate::*;
 use crate::environment::Game;
 use crate::Tcod;
 use crate::objects::*;
-use crate::graphics::gui::menu::inventory_menu;
+use crate::graphics::gui::menu::{ inventory_menu, powers_menu, equipment_menu, guess_menu };
+use crate::objects::items::equipment::Slot;
+
+pub mod travel;
+use travel::AutoMove;
 
 use serde::{ Serialize, Deserialize };
 
@@ -24,6 +28,21 @@ pub fn handle_keys(
     use PlayerAction::*;
 
     let player_alive = player.object.alive;
+
+    // Left clicking on an explored tile travels to it.
+    // The click is used up, so it isn't handled again on the next frame.
+    if tcod.mouse.lbutton_pressed && player_alive {
+        tcod.mouse.lbutton_pressed = false;
+        let (x, y) = (tcod.mouse.cx as i32, tcod.mouse.cy as i32);
+        if x >= 0 && y >= 0 && x < MAP_WIDTH && y < MAP_HEIGHT && game.map[x as usize][y as usize].explored {
+            game.auto_move = AutoMove::travel(game, items, &player.object, (x, y));
+            if game.auto_move.is_none() && (x, y) != player.object.pos() {
+                game.messages.add("You don't know of a way there.", WHITE);
+            }
+        }
+        return DidntTakeTurn;
+    }
+
     match (tcod.key, tcod.key.text(), player_alive) {
         // Movement keys
         ( Key { code: NumPad7, .. }, _, true) => {
@@ -70,7 +89,11 @@ pub fn handle_keys(
                 .values()
                 .any(|obj| obj.pos() == player.object.pos() && obj.name == "Stairs");
             if player_on_stairs {
-                next_level(tcod, game, &mut player.object, characters, items);
+                if game.stairs_sealed {
+                    game.messages.add("The stairs are sealed by a powerful force.", LIGHT_RED);
+                } else {
+                    next_level(tcod, game, &mut player.object, characters, items);
+                }
             }
             DidntTakeTurn
         }
@@ -102,6 +125,7 @@ pub fn handle_keys(
         ( Key { code: Text, .. }, "i", true) => {
             // Show the inventory.
             let inventory_index = inventory_menu(
+                game,
                 player,
                 "Press the key next to an item to use it, or any other to cancel.\n",
                 &mut tcod.root
@@ -112,11 +136,80 @@ pub fn handle_keys(
             TookTurn
         },
 
+        ( Key { code: Text, .. }, "s", true) => {
+            // Searches the surrounding tiles for anything hidden.
+            let (x, y) = player.object.pos();
+            let found_passage = Object::search_for_secrets(tcod, game, &player.object, SEARCH_CHANCE);
+            let found_trap = Object::reveal_traps(x, y, 1.5, SEARCH_CHANCE, game, items);
+            if !found_passage && !found_trap {
+                game.messages.add("You search the area, but find nothing.", WHITE);
+            }
+            TookTurn
+        },
+
+        ( Key { code: Text, .. }, "x", true) => {
+            // Attempts to disarm a nearby trap.
+            if Object::disarm_trap(&tcod.fov, game, characters, items, &mut player.object) {
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        },
+
+        ( Key { code: Text, .. }, "p", true) => {
+            // Prays at an altar, if the player is standing on one.
+            if Object::pray_at_altar(game, items, &mut player.object) {
+                TookTurn
+            } else {
+                DidntTakeTurn
+            }
+        },
+
+        ( Key { code: Text, .. }, "o", true) => {
+            // Starts exploring the level automatically.
+            // Each step is taken in the game loop, so monsters still get their turns.
+            game.auto_move = Some(AutoMove::explore(tcod, items, &player.object));
+            DidntTakeTurn
+        },
+
+        ( Key { code: Text, .. }, "t", true) => {
+            // Travels to the stairs, as long as they've been found.
+            let stairs = items
+                .values()
+                .find(|obj| obj.name == "Stairs" && game.map[obj.x as usize][obj.y as usize].explored)
+                .map(|obj| obj.pos());
+            match stairs {
+                Some(stairs) if stairs == player.object.pos() => {
+                    game.messages.add("You're already standing on the stairs.", WHITE);
+                },
+                Some(stairs) => {
+                    game.auto_move = AutoMove::travel(game, items, &player.object, stairs);
+                    if game.auto_move.is_none() {
+                        game.messages.add("You don't know of a way to the stairs.", WHITE);
+                    }
+                },
+                None => game.messages.add("You haven't found the stairs yet.", WHITE),
+            }
+            DidntTakeTurn
+        },
+
         ( Key { code: Text, .. }, "c", true) => {
             // Displays character information.
             let level = player.object.level;
             let level_up_xp = LEVEL_UP_BASE + player.object.level * LEVEL_UP_FACTOR;
             if let Some(fighter) = player.object.fighter.as_ref() {
+                // Lists what the player is wearing in each slot.
+                let gear: Vec<String> = [Slot::Weapon, Slot::Armor, Slot::Trinket]
+                    .iter()
+                    .map(|slot| {
+                        let worn = game.equipment
+                            .iter()
+                            .find(|item| item.equipment.map_or(false, |e| e.slot == *slot))
+                            .map_or("None".to_string(), |item| item.name.clone());
+                        format!("{}: {}", slot.name(), worn)
+                    })
+                    .collect();
+
                 let msg = format!(
                     "Character information
 
@@ -126,18 +219,94 @@ Experience to level up: {}
 
 Maximum HP: {}
 Attack: {}
-Defense: {}",
-                    level, fighter.exp, level_up_xp, fighter.max_hp, fighter.power, fighter.defense
+Defense: {}
+Accuracy: {}
+Evasion: {}
+
+{}
+
+Attacks: {} ({} hits, {} misses, {} critical)
+Damage dealt: {}
+Attacks dodged: {} of {}
+Damage received: {}",
+                    level, fighter.exp, level_up_xp, fighter.max_hp, fighter.power, fighter.defense,
+                    fighter.accuracy, fighter.evasion,
+                    gear.join("\n"),
+                    game.stats.attacks, game.stats.hits, game.stats.misses, game.stats.criticals,
+                    game.stats.damage_dealt,
+                    game.stats.dodged, game.stats.attacks_received,
+                    game.stats.damage_received
                 );
                 msgbox(&msg, CHARACTER_SCREEN_WIDTH, &mut tcod.root);
             }
             DidntTakeTurn
         },
 
+        ( Key { code: Text, .. }, "e", true) => {
+            // Shows the powers taken from slain monsters.
+            // Choosing one swaps it for the power there was no room for, or releases it if there isn't one.
+            let header = match game.unclaimed {
+                Some(power) => format!(
+                    "There is no room for {}.\nPress the key next to a power to replace it, or any other to keep your powers.\n",
+                    power.description()
+                ),
+                None => "Press the key next to a power to release it, or any other to cancel.\n".into(),
+            };
+            if let Some(index) = powers_menu(game, &header, &mut tcod.root) {
+                Object::replace_power(index, game, &mut player.object);
+            }
+            DidntTakeTurn
+        },
+
+        ( Key { code: Text, .. }, "r", true) => {
+            // Shows the equipment the player is wearing. If a piece is selected, take it off.
+            let has_room = player.inventory.as_ref().map_or(false, |inventory| inventory.len() < 26);
+            let equipment_index = equipment_menu(
+                game, "Press the key next to a piece of equipment to take it off, or any other to cancel.\n",
+                &mut tcod.root,
+            );
+            match equipment_index {
+                Some(_) if !has_room => {
+                    game.messages.add("Your inventory is full!", RED);
+                    DidntTakeTurn
+                },
+                Some(equipment_index) => {
+                    if let Some(item) = Object::unequip(equipment_index, game, &mut player.object) {
+                        player.inventory.as_mut().map(|inventory| inventory.push(item));
+                    }
+                    TookTurn
+                },
+                None => DidntTakeTurn,
+            }
+        },
+
+        ( Key { code: Text, .. }, "n", true) => {
+            // Names an unidentified item, after whichever kind the player guesses it is.
+            let inventory_index = inventory_menu(
+                game, &player, "Press the key next to an item to name it, or any other to cancel.\n",
+                &mut tcod.root,
+            );
+            let chosen = inventory_index
+                .and_then(|index| player.inventory.as_ref().map(|inventory| &inventory[index]))
+                .map(|item| (item.name.clone(), game.identity.name_of(item), item.item.as_ref().map(|i| i.category)));
+
+            if let Some((kind, shown, Some(category))) = chosen {
+                if game.identity.is_known(&kind) {
+                    game.messages.add(format!("You already know what the {} is.", shown), WHITE);
+                } else {
+                    let header = format!("What do you think the {} is?\n", shown);
+                    if let Some(guess) = guess_menu(game, category, &header, &mut tcod.root) {
+                        game.identity.guess(&kind, &guess);
+                    }
+                }
+            }
+            DidntTakeTurn
+        },
+
         ( Key { code: Text, .. }, "d", true) => {
             // Show the inventory. If an item is selected, drop it.
             let inventory_index = inventory_menu(
-                &player, "Press a listed key to drop an item, or another key to cancel.\n",
+                game, &player, "Press a listed key to drop an item, or another key to cancel.\n",
                 &mut tcod.root,
             );
             if let Some(inventory_index) = inventory_index {
@@ -0,0 +1,204 @@
+use crate::Tcod;
+use crate::environment::{ Game, MAP_WIDTH, MAP_HEIGHT };
+use crate::objects::{ Object, Character };
+use crate::pathing::{ Diagonals, walk_cost };
+use crate::pathing::astar::astar;
+use crate::pathing::dijkstra::DijkstraMap;
+
+use super::PlayerAction;
+
+use std::collections::HashMap;
+
+use tcod::colors::*;
+
+// Movement the player makes on their own, one step per turn, until something interrupts them.
+#[derive(Clone, Debug)]
+pub enum AutoMove {
+    Explore {
+        last_hp: i32,
+        seen_items: Vec<i32>,
+    },
+    Travel {
+        last_hp: i32,
+        path: Vec<(i32, i32)>,
+    },
+}
+
+impl AutoMove {
+    // Starts exploring. Items which can already be seen won't interrupt the player.
+    pub fn explore(tcod: &Tcod, items: &HashMap<i32, Object>, player: &Object) -> AutoMove {
+        AutoMove::Explore {
+            last_hp: player.fighter.map_or(0, |f| f.hp),
+            seen_items: visible_items(tcod, items),
+        }
+    }
+
+    // Starts travelling to a tile, along a path through explored tiles.
+    // Returns None if there is no known way to get there.
+    pub fn travel(game: &Game, items: &HashMap<i32, Object>, player: &Object, goal: (i32, i32)) -> Option<AutoMove> {
+        if goal == player.pos() {
+            return None;
+        }
+
+        let traps = revealed_traps(items);
+        let path = astar(player.pos(), goal, Diagonals::Always, |x, y| {
+            if !game.map[x as usize][y as usize].explored || traps.contains(&(x, y)) {
+                None
+            } else {
+                walk_cost(&game.map, x, y)
+            }
+        })?;
+
+        Some(AutoMove::Travel {
+            last_hp: player.fighter.map_or(0, |f| f.hp),
+            path: path,
+        })
+    }
+}
+
+// Returns the position of every trap the player knows about.
+fn revealed_traps(items: &HashMap<i32, Object>) -> Vec<(i32, i32)> {
+    items
+        .values()
+        .filter(|obj| obj.trap.map_or(false, |t| !t.hidden))
+        .map(|obj| obj.pos())
+        .collect()
+}
+
+// Returns the ids of every item the player can currently see.
+fn visible_items(tcod: &Tcod, items: &HashMap<i32, Object>) -> Vec<i32> {
+    items
+        .iter()
+        .filter(|(_, obj)| obj.item.is_some() && tcod.fov.is_in_fov(obj.x, obj.y))
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+// Checks for anything which should stop the player from moving on their own.
+// Returns the reason for stopping, if there is one.
+fn interruption(tcod: &Tcod, characters: &[Character], player: &Object, last_hp: i32) -> Option<String> {
+    if let Some(monster) = characters
+        .iter()
+        .find(|c| c.object.alive && c.object.ai.is_some() && tcod.fov.is_in_fov(c.object.x, c.object.y)) {
+        return Some(format!("{} comes into view.", monster.object.name));
+    }
+
+    if player.fighter.map_or(false, |f| f.hp < last_hp) {
+        return Some("You have been hurt.".into());
+    }
+    None
+}
+
+// Finds the step which leads towards the closest unexplored tile that can be reached.
+// Revealed traps are walked around.
+fn explore_step(game: &Game, items: &HashMap<i32, Object>, player: &Object) -> Option<(i32, i32)> {
+    let traps = revealed_traps(items);
+    let cost = |x: i32, y: i32| {
+        if traps.contains(&(x, y)) {
+            None
+        } else {
+            walk_cost(&game.map, x, y)
+        }
+    };
+
+    let mut unexplored = vec![];
+    for x in 0..MAP_WIDTH {
+        for y in 0..MAP_HEIGHT {
+            let tile = &game.map[x as usize][y as usize];
+            if !tile.explored && !tile.blocked {
+                unexplored.push((x, y));
+            }
+        }
+    }
+
+    let dijkstra = DijkstraMap::new(&unexplored, Diagonals::Always, None, &cost);
+    dijkstra.downhill(player.x, player.y, &cost)
+}
+
+// Moves the player a single step, and returns whether or not a turn was taken.
+// Once the player has stopped, the auto move is cleared.
+pub fn auto_move_step(
+    tcod: &Tcod,
+    game: &mut Game,
+    characters: &mut Vec<Character>,
+    items: &HashMap<i32, Object>,
+    player: &mut Character,
+) -> PlayerAction {
+    let auto_move = match game.auto_move.take() {
+        Some(auto_move) => auto_move,
+        None => return PlayerAction::DidntTakeTurn,
+    };
+
+    match auto_move {
+        AutoMove::Explore { last_hp, mut seen_items } => {
+            let spotted = visible_items(tcod, items)
+                .into_iter()
+                .find(|id| !seen_items.contains(id))
+                .map(|id| format!("You spot a {}.", game.identity.name_of(&items[&id])));
+            if let Some(reason) = interruption(tcod, characters, &player.object, last_hp).or(spotted) {
+                game.messages.add(format!("You stop exploring. {}", reason), LIGHT_GREY);
+                return PlayerAction::DidntTakeTurn;
+            }
+
+            match explore_step(game, items, &player.object) {
+                Some((x, y)) => {
+                    let (dx, dy) = (x - player.object.x, y - player.object.y);
+                    Object::player_move_or_attack(dx, dy, game, characters, &mut player.object);
+
+                    seen_items.extend(visible_items(tcod, items));
+                    game.auto_move = Some(AutoMove::Explore {
+                        last_hp: player.object.fighter.map_or(0, |f| f.hp),
+                        seen_items: seen_items,
+                    });
+                    PlayerAction::TookTurn
+                },
+                None => {
+                    game.messages.add("There's nothing left to explore.", LIGHT_GREY);
+                    PlayerAction::DidntTakeTurn
+                },
+            }
+        },
+
+        AutoMove::Travel { last_hp, mut path } => {
+            if let Some(reason) = interruption(tcod, characters, &player.object, last_hp) {
+                game.messages.add(format!("You stop travelling. {}", reason), LIGHT_GREY);
+                return PlayerAction::DidntTakeTurn;
+            }
+
+            // If the player has been moved off the path, such as by a trap or a knockback,
+            // a new path is found to the same destination.
+            let (next_x, next_y) = path[0];
+            if (next_x - player.object.x).abs() > 1 || (next_y - player.object.y).abs() > 1 {
+                let goal = path[path.len() - 1];
+                if goal == player.object.pos() {
+                    return PlayerAction::DidntTakeTurn;
+                }
+                path = match AutoMove::travel(game, items, &player.object, goal) {
+                    Some(AutoMove::Travel { path, .. }) => path,
+                    _ => {
+                        game.messages.add("You stop travelling. You no longer know the way.", LIGHT_GREY);
+                        return PlayerAction::DidntTakeTurn;
+                    },
+                };
+            }
+
+            let (x, y) = path.remove(0);
+            if Object::is_blocked(x, y, &game.map, characters) {
+                game.messages.add("You stop travelling. Something is in the way.", LIGHT_GREY);
+                return PlayerAction::DidntTakeTurn;
+            }
+
+            let (dx, dy) = (x - player.object.x, y - player.object.y);
+            Object::player_move_or_attack(dx, dy, game, characters, &mut player.object);
+
+            // Keeps going until the end of the path has been reached.
+            if !path.is_empty() {
+                game.auto_move = Some(AutoMove::Travel {
+                    last_hp: player.object.fighter.map_or(0, |f| f.hp),
+                    path: path,
+                });
+            }
+            PlayerAction::TookTurn
+        },
+    }
+}
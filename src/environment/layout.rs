@@ -0,0 +1,109 @@
+use crate::environment::{ Map, MAP_WIDTH, MAP_HEIGHT };
+use crate::environment::map::rooms::{ Room, RoomRole };
+
+use std::collections::VecDeque;
+
+use serde::{ Serialize, Deserialize };
+
+// Everything that was learnt about the shape of a level while it was being generated.
+// Each room is its own region, and any other open tiles are split into corridors.
+// Region ids start with the rooms, in order, followed by the corridors.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LevelLayout {
+    pub rooms: Vec<Room>,
+    pub corridors: Vec<Vec<(i32, i32)>>,
+    pub points: Vec<(i32, i32)>,
+    pub regions: Vec<Vec<Option<usize>>>,
+    pub start: (i32, i32),
+    pub stairs: (i32, i32),
+}
+
+// Secret passages are counted as open, so hidden tunnels still belong to a corridor.
+fn is_open(map: &Map, x: i32, y: i32) -> bool {
+    let tile = &map[x as usize][y as usize];
+    tile.empty || tile.secret_path
+}
+
+impl LevelLayout {
+    // Creates the layout record for a finished map, working out which region each tile belongs to.
+    pub fn new(map: &Map, rooms: Vec<Room>, points: Vec<(i32, i32)>, start: (i32, i32), stairs: (i32, i32)) -> LevelLayout {
+        let mut regions = vec![vec![None; MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+
+        // Room tiles are marked first. If rooms overlap, the earlier room keeps the tile.
+        for (id, room) in rooms.iter().enumerate() {
+            for x in room.rect.x1 + 1..room.rect.x2 {
+                for y in room.rect.y1 + 1..room.rect.y2 {
+                    if x > 0 && y > 0 && x < MAP_WIDTH && y < MAP_HEIGHT &&
+                    regions[x as usize][y as usize].is_none() && is_open(map, x, y) {
+                        regions[x as usize][y as usize] = Some(id);
+                    }
+                }
+            }
+        }
+
+        // Every remaining group of connected open tiles is flood filled into a corridor.
+        let mut corridors = vec![];
+        for x in 0..MAP_WIDTH {
+            for y in 0..MAP_HEIGHT {
+                if regions[x as usize][y as usize].is_some() || !is_open(map, x, y) {
+                    continue;
+                }
+
+                let region = rooms.len() + corridors.len();
+                let mut tiles = vec![];
+                let mut frontier = VecDeque::new();
+                regions[x as usize][y as usize] = Some(region);
+                frontier.push_back((x, y));
+
+                while let Some((cx, cy)) = frontier.pop_front() {
+                    tiles.push((cx, cy));
+                    for (dx, dy) in &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                        let (nx, ny) = (cx + dx, cy + dy);
+                        if nx < 0 || ny < 0 || nx >= MAP_WIDTH || ny >= MAP_HEIGHT {
+                            continue;
+                        }
+                        if regions[nx as usize][ny as usize].is_none() && is_open(map, nx, ny) {
+                            regions[nx as usize][ny as usize] = Some(region);
+                            frontier.push_back((nx, ny));
+                        }
+                    }
+                }
+                corridors.push(tiles);
+            }
+        }
+
+        LevelLayout {
+            rooms: rooms,
+            corridors: corridors,
+            points: points,
+            regions: regions,
+            start: start,
+            stairs: stairs,
+        }
+    }
+
+    // Returns the region id of a tile, or None if it is solid.
+    pub fn region_at(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= MAP_WIDTH || y >= MAP_HEIGHT {
+            return None;
+        }
+        self.regions[x as usize][y as usize]
+    }
+
+    // Finds the room which contains the given tile, if there is one.
+    pub fn room_at(&self, x: i32, y: i32) -> Option<&Room> {
+        self.region_at(x, y).and_then(|region| self.rooms.get(region))
+    }
+
+    // Finds the corridor which contains the given tile, if there is one.
+    pub fn corridor_at(&self, x: i32, y: i32) -> Option<&Vec<(i32, i32)>> {
+        self.region_at(x, y)
+            .filter(|region| *region >= self.rooms.len())
+            .and_then(|region| self.corridors.get(region - self.rooms.len()))
+    }
+
+    // Boss floors are the only floors which contain an arena.
+    pub fn has_arena(&self) -> bool {
+        self.rooms.iter().any(|room| room.role == RoomRole::Arena)
+    }
+}
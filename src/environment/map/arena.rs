@@ -0,0 +1,55 @@
+use crate::graphics::render_map;
+use crate::Tcod;
+use crate::environment::map::{ create_room, create_h_tunnel };
+use crate::environment::*;
+
+// Creates a boss floor.
+// A small antechamber leads into one large arena, which is broken up by a ring of pillars.
+pub fn arena(
+    rooms: &mut Vec<Rect>,
+    mut map: &mut Map,
+    colors: &[Color; 7],
+    player: &mut Object,
+    tcod: &mut Tcod,
+    should_render: bool,
+) {
+    // The antechamber sits against the left edge of the map.
+    let entrance = Rect::new(2, MAP_HEIGHT / 2 - 4, 10, 8);
+    create_room(entrance, &mut map, &colors);
+
+    // The arena fills most of the remaining space.
+    let arena = Rect::new(20, 3, MAP_WIDTH - 24, MAP_HEIGHT - 7);
+    create_room(arena, &mut map, &colors);
+
+    if should_render {
+        render_map(tcod, map, 4);
+    }
+
+    // A single corridor joins the two rooms, so there is only one way in.
+    let (entrance_x, entrance_y) = entrance.center();
+    let (arena_x, _) = arena.center();
+    create_h_tunnel(entrance_x, arena_x, entrance_y, &mut map, &colors);
+
+    // Places pillars in a ring around the center of the arena, to provide some cover.
+    let (center_x, center_y) = arena.center();
+    let ring_radius = 12.0;
+    for step in 0..16 {
+        let angle = step as f32 / 16.0 * 2.0 * std::f32::consts::PI;
+        let x = center_x + (angle.cos() * ring_radius * 1.5).round() as i32;
+        let y = center_y + (angle.sin() * ring_radius * 0.8).round() as i32;
+        // Pillars are left off the corridor row, so the way in stays clear.
+        if arena.contains(x, y) && y != entrance_y {
+            map[x as usize][y as usize] = Tile::wall(colors);
+        }
+    }
+
+    if should_render {
+        render_map(tcod, map, 4);
+    }
+
+    // The player starts in the antechamber.
+    player.set_pos(entrance_x, entrance_y);
+
+    rooms.push(entrance);
+    rooms.push(arena);
+}
@@ -3,6 +3,7 @@ use crate::environment::*;
 use crate::Tcod;
 
 use std::cmp;
+use std::collections::VecDeque;
 
 pub fn maze(
     map: &mut Map,
@@ -17,7 +18,7 @@ pub fn maze(
         for y in 0..MAP_HEIGHT {
             if x % 2 == 1 && y % 2 == 1 {
                 map[x as usize][y as usize] = Tile::empty(colors);
-                maze.points.push(Point::new(x as u32, y as u32));
+                maze.add_point(Point::new(x as u32, y as u32));
             }
         }
         if should_render {
@@ -69,8 +70,9 @@ pub fn maze(
 struct Maze {
     x: u32,
     y: u32,
-    points: Vec<Point>,
-    backtrack: Vec<(u32, u32)>,
+    points: Vec<Option<Point>>, // Indexed by x * height + y, only odd tiles hold a point.
+    unvisited: u32,
+    backtrack: VecDeque<(u32, u32)>,
     position: (u32, u32),
 }
 impl Maze {
@@ -78,12 +80,21 @@ impl Maze {
         Maze {
             x,
             y,
-            points: Vec::new(),
-            backtrack: Vec::new(),
+            points: vec![None; (x * y) as usize],
+            unvisited: 0,
+            backtrack: VecDeque::new(),
             position: (1, 1),
         }
     }
 
+    fn add_point(&mut self, point: Point) {
+        let index = (point.x * self.y + point.y) as usize;
+        if self.points[index].is_none() {
+            self.unvisited += 1;
+        }
+        self.points[index] = Some(point);
+    }
+
     fn pos_move(&mut self, point: Point) {
         let (x, y) = self.position;
         match self.get_point(point.x, point.y) {
@@ -103,41 +114,30 @@ impl Maze {
     }
 
     fn get_point(&self, x: u32, y: u32) -> Option<Point> {
-        let indexed = self.points.iter().enumerate();
-        for (_, point) in indexed {
-            if point.x == x && point.y == y {
-                return Some(*point)
-            }
+        if x >= self.x || y >= self.y {
+            return None
         }
-        None
+        self.points[(x * self.y + y) as usize]
     }
 
     fn visit(&mut self, point: Point) {
-        let mut p_index = 0;
-        let indexed = self.points.iter().enumerate();
-        for (id, searching) in indexed {
-            if searching.x == point.x && searching.y == point.y {
-                p_index = id;
+        let index = (point.x * self.y + point.y) as usize;
+        if let Some(searching) = self.points[index].as_mut() {
+            if !searching.visited {
+                searching.visit();
+                self.unvisited -= 1;
             }
+            self.backtrack.push_back(searching.get_xy());
         }
-        self.points[p_index].visit();
-        self.backtrack.push(self.points[p_index].get_xy());
     }
 
     fn count_visited(&self) -> u32 {
-        let mut count = 0;
-        for point in &self.points {
-            if !point.visited {
-                count += 1;
-            }
-        }
-        count
+        self.unvisited
     }
 
     fn backstep(&mut self) {
-        if !self.backtrack.is_empty() {
-            self.position = self.backtrack[0];
-            self.backtrack.remove(0);
+        if let Some(position) = self.backtrack.pop_front() {
+            self.position = position;
         }
     }
 
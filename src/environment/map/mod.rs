@@ -3,6 +3,8 @@ pub mod modifiers;
 pub mod drunk_walk;
 pub mod cellular_automata;
 pub mod maze;
+pub mod arena;
+pub mod rooms;
 
 pub mod tiles;
 
@@ -65,6 +67,11 @@ impl Rect {
         (center_x, center_y)
     }
 
+    // Checks to see if a tile is part of the floor of a Rect.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x > self.x1 && x < self.x2 && y > self.y1 && y < self.y2
+    }
+
     // Checks to see if one Rect intersects with another.
     pub fn intersects_with(&self, other: &Rect) -> bool {
         // Returns true if this rectangle intersects with another
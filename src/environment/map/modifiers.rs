@@ -3,6 +3,7 @@ use crate::Tcod;
 use crate::environment::{ Map, MAP_WIDTH, MAP_HEIGHT };
 use crate::environment::tiles::Tile;
 use crate::environment::map::Rect;
+use crate::objects::Object;
 
 use tcod::colors::*;
 use rand::*;
@@ -277,3 +278,100 @@ pub fn pillars(rooms: &Vec<Rect>, map: &mut Map, colors: &[Color; 7], tcod: &mut
         }
     }
 }
+
+// Places lone pillars throughout large open areas, for maps which aren't made of rooms.
+pub fn scatter_pillars(map: &mut Map, colors: &[Color; 7], tcod: &mut Tcod, should_render: bool) {
+    // Pillars are spaced out on a grid, so they never end up touching each other.
+    let spacing = 4;
+
+    for x in (2..MAP_WIDTH - 2).step_by(spacing) {
+        for y in (2..MAP_HEIGHT - 2).step_by(spacing) {
+            // A pillar is only placed if every tile around it is open, so it can never block a path.
+            let open = (x - 1..x + 2).all(|nx| {
+                (y - 1..y + 2).all(|ny| map[nx as usize][ny as usize].empty)
+            });
+
+            if open && rand::random() {
+                map[x as usize][y as usize] = Tile::wall(colors);
+            }
+        }
+    }
+
+    if should_render {
+        render_map(tcod, map, 4);
+    }
+}
+
+// Prefabricated vault layouts.
+// '#' is a wall, '.' is floor, and '+' is the entrance, which must sit on the bottom row.
+const VAULT_PREFABS: [&[&str]; 2] = [
+    &[
+        "#########",
+        "#.......#",
+        "#.#...#.#",
+        "#.......#",
+        "####+####",
+    ],
+    &[
+        "#######",
+        "#.....#",
+        "#.###.#",
+        "#.#.#.#",
+        "#.....#",
+        "###+###",
+    ],
+];
+
+// Stamps a prefabricated vault onto the map, with its entrance opening onto existing floor.
+// Vaults are only carved out of solid rock, so they can never cut the floor around them apart.
+// The rect of each placed vault is added to the vault vector.
+pub fn vaults(
+    vaults: &mut Vec<Rect>,
+    rooms: &Vec<Rect>,
+    player: &Object,
+    map: &mut Map,
+    colors: &[Color; 7],
+    tcod: &mut Tcod,
+    should_render: bool,
+) {
+    let prefab = VAULT_PREFABS[rand::thread_rng().gen_range(0, VAULT_PREFABS.len())];
+    let width = prefab[0].len() as i32;
+    let height = prefab.len() as i32;
+    let max_tries = 200;
+
+    for _ in 0..max_tries {
+        // Random position, which leaves room for the entrance to open onto the tile below.
+        let x = rand::thread_rng().gen_range(1, MAP_WIDTH - width - 1);
+        let y = rand::thread_rng().gen_range(1, MAP_HEIGHT - height - 2);
+        let vault = Rect::new(x, y, width - 1, height - 1);
+
+        // Finds the entrance, and checks that it leads onto existing floor.
+        let door_x = x + prefab[(height - 1) as usize].find('+').unwrap_or(0) as i32;
+        let door_y = y + height - 1;
+        let leads_somewhere = map[door_x as usize][(door_y + 1) as usize].empty;
+
+        // The vault mustn't cover any rooms, the player, or any floor.
+        let overlaps = rooms.iter().chain(vaults.iter()).any(|other| vault.intersects_with(other));
+        let solid = (x..x + width).all(|tile_x| (y..y + height).all(|tile_y| !map[tile_x as usize][tile_y as usize].empty));
+        let covers_player = player.x >= vault.x1 && player.x <= vault.x2 && player.y >= vault.y1 && player.y <= vault.y2;
+
+        if leads_somewhere && solid && !overlaps && !covers_player {
+            // Stamps the prefab onto the map.
+            for (dy, row) in prefab.iter().enumerate() {
+                for (dx, symbol) in row.chars().enumerate() {
+                    let (tile_x, tile_y) = ((x + dx as i32) as usize, (y + dy as i32) as usize);
+                    map[tile_x][tile_y] = match symbol {
+                        '#' => Tile::wall(colors),
+                        _ => Tile::empty(colors),
+                    };
+                }
+            }
+            vaults.push(vault);
+
+            if should_render {
+                render_map(tcod, map, 4);
+            }
+            break;
+        }
+    }
+}
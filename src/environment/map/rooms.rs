@@ -0,0 +1,179 @@
+use crate::graphics::render_map;
+use crate::Tcod;
+use crate::environment::Map;
+use crate::environment::map::{ Rect, create_secret_h_tunnel, create_secret_v_tunnel };
+
+use rand::distributions::{ IndependentSample, Weighted, WeightedChoice };
+use serde::{ Serialize, Deserialize };
+
+use tcod::colors::*;
+
+// The purpose a room serves, which decides what is spawned inside of it.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RoomRole {
+    Ordinary,
+    Treasure,
+    Den,
+    Shrine,
+    Library,
+    Stairs,
+    Arena,
+}
+
+impl RoomRole {
+    // Text used when announcing that the player has entered a room.
+    // Ordinary rooms aren't worth mentioning.
+    pub fn description(&self) -> Option<&'static str> {
+        match self {
+            RoomRole::Ordinary => None,
+            RoomRole::Treasure => Some("a treasure room"),
+            RoomRole::Den => Some("a monster den"),
+            RoomRole::Shrine => Some("a shrine"),
+            RoomRole::Library => Some("a library"),
+            RoomRole::Stairs => Some("the stairwell"),
+            RoomRole::Arena => Some("the arena. The air crackles with menace"),
+        }
+    }
+}
+
+// A rect, tagged with its role.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Room {
+    pub rect: Rect,
+    pub role: RoomRole,
+}
+
+// Gives each of the sorted rooms a role.
+// The room the player starts in is always ordinary, wherever sorting has moved it to.
+// The last of the other rooms holds the stairs.
+pub fn assign_room_roles(rects: &[Rect], start: (i32, i32)) -> Vec<Room> {
+    let mut role_chances = [
+        Weighted { weight: 60, item: RoomRole::Ordinary },
+        Weighted { weight: 8, item: RoomRole::Treasure },
+        Weighted { weight: 12, item: RoomRole::Den },
+        Weighted { weight: 8, item: RoomRole::Shrine },
+        Weighted { weight: 12, item: RoomRole::Library },
+    ];
+    let role_choice = WeightedChoice::new(&mut role_chances);
+
+    let (start_x, start_y) = start;
+    let stairs_room = rects.iter().rposition(|rect| !rect.contains(start_x, start_y));
+    rects.iter().enumerate().map(|(id, rect)| {
+        let role = if rect.contains(start_x, start_y) {
+            RoomRole::Ordinary
+        } else if Some(id) == stairs_room {
+            RoomRole::Stairs
+        } else {
+            role_choice.ind_sample(&mut rand::thread_rng())
+        };
+        Room { rect: *rect, role }
+    }).collect()
+}
+
+// Checks if any tile in the walls around a rect can be walked through, without first being found.
+fn has_open_wall(rect: Rect, map: &Map) -> bool {
+    (rect.x1..rect.x2 + 1).any(|x| (rect.y1..rect.y2 + 1).any(|y| {
+        let on_wall = x == rect.x1 || x == rect.x2 || y == rect.y1 || y == rect.y2;
+        on_wall && map[x as usize][y as usize].empty
+    }))
+}
+
+// Ordinary tunnels are free to cut straight through a treasure room, which would let the player walk in without searching.
+// Treasure rooms which were cut into are part of the main path, so they are made ordinary instead.
+pub fn expose_crossed_treasure_rooms(rooms: &mut [Room], map: &Map) {
+    for room in rooms.iter_mut().filter(|room| room.role == RoomRole::Treasure) {
+        if has_open_wall(room.rect, map) {
+            room.role = RoomRole::Ordinary;
+        }
+    }
+}
+
+// Returns the rects of all rooms which should be joined by regular tunnels.
+// Treasure rooms are left out, as they are hidden away separately.
+pub fn connected_rects(rooms: &[Room]) -> Vec<Rect> {
+    rooms.iter()
+        .filter(|room| room.role != RoomRole::Treasure)
+        .map(|room| room.rect)
+        .collect()
+}
+
+// Joins each treasure room to the closest connected room, using only secret tunnels.
+pub fn hide_treasure_rooms(rooms: &[Room], mut map: &mut Map, colors: &[Color; 7], tcod: &mut Tcod, should_render: bool) {
+    let connected = connected_rects(rooms);
+
+    for room in rooms.iter().filter(|room| room.role == RoomRole::Treasure) {
+        let (x1, y1) = room.rect.center();
+
+        // Finds the closest room, which is reachable without any searching.
+        let closest = connected.iter().min_by_key(|other| {
+            let (x2, y2) = other.center();
+            (x2 - x1).pow(2) + (y2 - y1).pow(2)
+        });
+
+        if let Some(other) = closest {
+            let (x2, y2) = other.center();
+            if rand::random() {
+                create_secret_h_tunnel(x1, x2, y1, &mut map, &colors);
+                create_secret_v_tunnel(y1, y2, x2, &mut map, &colors);
+            } else {
+                create_secret_v_tunnel(y1, y2, x1, &mut map, &colors);
+                create_secret_h_tunnel(x1, x2, y2, &mut map, &colors);
+            }
+        }
+
+        if should_render {
+            render_map(tcod, map, 4);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::{ MAP_WIDTH, MAP_HEIGHT };
+    use crate::environment::map::{ create_room, create_h_tunnel };
+    use crate::environment::map::tiles::Tile;
+
+    const COLORS: [Color; 7] = [GREY, DARK_SEPIA, SEPIA, LIGHT_SEPIA, DARKER_SEPIA, DARK_GREY, DARKEST_GREY];
+
+    fn treasure_room(map: &mut Map) -> Vec<Room> {
+        let rect = Rect::new(20, 10, 8, 8);
+        create_room(rect, map, &COLORS);
+        vec![Room { rect: rect, role: RoomRole::Treasure }]
+    }
+
+    fn solid_map() -> Map {
+        vec![vec![Tile::wall(&COLORS); MAP_HEIGHT as usize]; MAP_WIDTH as usize]
+    }
+
+    #[test]
+    fn tunnel_through_treasure_room_exposes_it() {
+        let mut map = solid_map();
+        let mut rooms = treasure_room(&mut map);
+        create_h_tunnel(10, 40, 14, &mut map, &COLORS);
+
+        expose_crossed_treasure_rooms(&mut rooms, &map);
+        assert_eq!(rooms[0].role, RoomRole::Ordinary);
+    }
+
+    #[test]
+    fn secret_tunnel_keeps_treasure_room_hidden() {
+        let mut map = solid_map();
+        let mut rooms = treasure_room(&mut map);
+        create_secret_h_tunnel(10, 24, 14, &mut map, &COLORS);
+
+        expose_crossed_treasure_rooms(&mut rooms, &map);
+        assert_eq!(rooms[0].role, RoomRole::Treasure);
+    }
+
+    #[test]
+    fn start_room_is_never_given_a_role() {
+        let rects: Vec<Rect> = (0..5).map(|i| Rect::new(2 + i * 14, 4, 8, 8)).collect();
+        for start in rects.iter().map(|rect| rect.center()) {
+            let rooms = assign_room_roles(&rects, start);
+            let start_room = rooms.iter().find(|room| room.rect.contains(start.0, start.1)).unwrap();
+            assert_eq!(start_room.role, RoomRole::Ordinary);
+            assert_eq!(rooms.iter().filter(|room| room.role == RoomRole::Stairs).count(), 1);
+        }
+    }
+}
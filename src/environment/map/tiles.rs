@@ -23,6 +23,8 @@ pub struct Tile {
     pub found: bool,
     pub blocked: bool,
     pub explored: bool,
+    #[serde(skip)]
+    pub visible: bool, // Whether the player could see the tile, the last time the screen was drawn.
     pub block_sight: bool,
     pub color_light: Color,
     pub color_dark: Color,
@@ -54,6 +56,7 @@ impl Tile {
             found: false,
             blocked: false,
             explored: false,
+            visible: false,
             block_sight: false,
             color_light: color_light,
             color_dark: color_dark,
@@ -74,6 +77,7 @@ impl Tile {
             found: false,
             blocked: true,
             explored: false,
+            visible: false,
             block_sight: true,
             color_light: color_light,
             color_dark: color_dark,
@@ -82,11 +86,9 @@ impl Tile {
     }
 
     pub fn hidden_passage(colors: &[Color; 7]) -> Tile {
-        // Secret path colors are the variants pulled from the base wall color.
-        let color_light = match rand::thread_rng().gen_range(1, 3) {
-            1 => colors[LIGHT_WALL_COLOR + V_ONE],
-            _ => colors[LIGHT_WALL_COLOR + V_TWO],
-        };
+        // Until it is discovered, a secret path is indistinguishable from a wall.
+        // It uses the same colors, and blocks both movement and sight.
+        let color_light = colors[LIGHT_WALL_COLOR];
         let color_dark = color_light - colors[DARKNESS_MODIFIER];
 
         // Tile is returned.
@@ -95,8 +97,9 @@ impl Tile {
             wall: false,
             secret_path: true,
             found: false,
-            blocked: false,
+            blocked: true,
             explored: false,
+            visible: false,
             block_sight: true,
             color_light: color_light,
             color_dark: color_dark,
@@ -104,6 +107,18 @@ impl Tile {
         }
     }
 
+    // Reveals a secret path, turning it into a passable tile.
+    // The path is shaded slightly darker than the surrounding walls, so it stands out once found.
+    pub fn discover(&mut self) {
+        if self.secret_path && !self.found {
+            self.found = true;
+            self.blocked = false;
+            self.block_sight = false;
+            self.color_light = self.color_light * 0.7;
+            self.color_dark = self.color_dark * 0.7;
+        }
+    }
+
     // Found, and Path are just debug tiles.
     pub fn found() -> Tile {
         Tile {
@@ -113,6 +128,7 @@ impl Tile {
             found: true,
             blocked: false,
             explored: false,
+            visible: false,
             block_sight: true,
             color_light: tcod::colors::DARK_BLUE,
             color_dark: tcod::colors::DARK_RED,
@@ -128,6 +144,7 @@ impl Tile {
             found: true,
             blocked: false,
             explored: false,
+            visible: false,
             block_sight: true,
             color_light: tcod::colors::LIGHT_GREEN,
             color_dark: tcod::colors::LIGHT_GREEN,
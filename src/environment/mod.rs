@@ -2,32 +2,31 @@ pub mod map;
 use map::tiles::Tile;
 use map::*;
 
-use map::{ // List of map gen variants go here
-    rectangles::rectangles,
-    drunk_walk::drunk_walk,
-    cellular_automata::cellular_automata,
-    maze::maze,
-    modifiers::*,
-};
+pub mod schedule;
+use schedule::LevelSchedule;
+
+pub mod layout;
+use layout::LevelLayout;
+
+pub mod pipeline;
+use pipeline::MapBuilder;
 
 pub mod spawner;
-use spawner::{
-    rooms_spawner,
-    no_rooms_spawner,
-    maze_spawner,
-};
 
 use crate::{ Tcod, initialise_fov };
 use crate::graphics::gui::Messages;
+use crate::controls::travel::AutoMove;
 use crate::objects::{ Object, Character };
+use crate::objects::powers::Power;
+use crate::objects::combat::CombatStats;
+use crate::objects::npc::Remains;
+use crate::objects::items::table::ItemTable;
+use crate::objects::items::identify::Identification;
 use crate::graphics::gen_colors;
-use crate::pathing::remove_inaccessible_tiles;
-use crate::environment::spawner::{ Transition, from_dungeon_level };
 
 use std::collections::HashMap;
 
 use rand::*;
-use rand::distributions::{ IndependentSample, Weighted, WeightedChoice };
 
 use serde::{ Serialize, Deserialize };
 
@@ -38,6 +37,7 @@ use tcod::colors::*;
 pub const FOV_ALGO: FovAlgorithm = FovAlgorithm::Basic; // Default FOV Algorithm
 pub const FOV_LIGHT_WALLS: bool = true;
 pub const TORCH_RADIUS: i32 = 10;
+pub const BLIND_RADIUS: i32 = 1; // How far a blinded player can still see.
 
 // Size of the map
 pub const MAP_WIDTH: i32 = 81;
@@ -51,7 +51,7 @@ const MAX_ROOMS: i32 = 18;
 const GROUND_COLOR: usize = 3;
 
 // Bool value to decide whether or not the map generation should be rendered or not.
-const RENDER: bool = false;
+pub const RENDER: bool = false;
 
 // Map type definition.
 pub type Map = Vec<Vec<Tile>>;
@@ -60,8 +60,27 @@ pub type Map = Vec<Vec<Tile>>;
 #[derive(Serialize, Deserialize)]
 pub struct Game {
     pub map: Map,
+    pub layout: LevelLayout,
     pub messages: Messages,
     pub dungeon_level: u32,
+    pub stairs_sealed: bool,
+    pub powers: Vec<Power>,
+    pub unclaimed: Option<Power>, // The latest power which there was no free slot for.
+    pub stats: CombatStats,
+    pub equipment: Vec<Object>, // Equipment the player is wearing, with one piece in each slot.
+    pub identity: Identification, // What unidentified items look like this run, and what the player knows of them.
+    #[serde(skip, default = "LevelSchedule::built_in")]
+    pub schedule: LevelSchedule, // Loaded once per game, and again when a game is loaded.
+    #[serde(skip, default = "ItemTable::built_in")]
+    pub item_table: ItemTable, // Loaded once per game, and again when a game is loaded.
+    #[serde(skip)]
+    pub auto_move: Option<AutoMove>,
+    #[serde(skip)]
+    pub noises: Vec<(i32, i32, i32)>, // (x, y, radius) of each noise made this turn.
+    #[serde(skip)]
+    pub essences: Vec<(MapTheme, i32)>, // Essences left behind by monsters slain this turn.
+    #[serde(skip)]
+    pub remains: Vec<Remains>, // Death behaviors of monsters slain this turn.
 }
 
 impl Game {
@@ -73,11 +92,77 @@ impl Game {
         player: &mut Object,
         tcod: &mut Tcod
     ) -> Game {
-        let map = make_map(player, &mut characters, &mut items, 1, tcod);
+        let mut messages = Messages::new();
+        let schedule = LevelSchedule::load(&mut messages);
+        let item_table = ItemTable::load(&mut messages);
+        let identity = Identification::new(&item_table);
+        let (map, layout) = make_map(player, &mut characters, &mut items, &schedule, &item_table, &identity, 1, tcod);
+        let stairs_sealed = layout.has_arena();
         Game {
             map: map,
+            layout: layout,
+            messages: messages,
+            dungeon_level: 1,
+            stairs_sealed: stairs_sealed,
+            powers: vec![],
+            unclaimed: None,
+            stats: CombatStats::default(),
+            equipment: vec![],
+            identity: identity,
+            schedule: schedule,
+            item_table: item_table,
+            auto_move: None,
+            noises: vec![],
+            essences: vec![],
+            remains: vec![],
+        }
+    }
+
+    // Makes a noise, which can wake up any sleeping monster within its radius.
+    pub fn make_noise(&mut self, x: i32, y: i32, radius: i32) {
+        self.noises.push((x, y, radius));
+    }
+
+    // Announces the role of a room when the player moves into it from somewhere else.
+    pub fn announce_room(&mut self, from: (i32, i32), to: (i32, i32)) {
+        let previous = self.layout.room_at(from.0, from.1).map(|room| room.rect);
+        let description = match self.layout.room_at(to.0, to.1) {
+            Some(room) if Some(room.rect) != previous => room.role.description(),
+            _ => None,
+        };
+
+        if let Some(description) = description {
+            self.messages.add(format!("You enter {}.", description), LIGHT_GREY);
+        }
+    }
+}
+
+#[cfg(test)]
+impl Game {
+    // A game on a single open floor, walled in at the edges, for tests which need somewhere to play out.
+    pub fn for_tests() -> Game {
+        let colors = [GREY, DARK_SEPIA, SEPIA, LIGHT_SEPIA, DARKER_SEPIA, DARK_GREY, DARKEST_GREY];
+        let mut map = vec![vec![Tile::wall(&colors); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+        create_room(Rect::new(0, 0, MAP_WIDTH - 1, MAP_HEIGHT - 1), &mut map, &colors);
+        let layout = LevelLayout::new(&map, vec![], vec![], (1, 1), (MAP_WIDTH - 2, MAP_HEIGHT - 2));
+        let item_table = ItemTable::built_in();
+        Game {
+            map: map,
+            layout: layout,
             messages: Messages::new(),
             dungeon_level: 1,
+            stairs_sealed: false,
+            powers: vec![],
+            unclaimed: None,
+            stats: CombatStats::default(),
+            equipment: vec![],
+            identity: Identification::new(&item_table),
+            schedule: LevelSchedule::built_in(),
+            item_table: item_table,
+            auto_move: None,
+            noises: vec![],
+            essences: vec![],
+            remains: vec![],
         }
     }
 }
@@ -105,11 +190,22 @@ pub fn next_level(
 
     // Updates the dungeon depth, makes new dungeon map, and re-generates FOV map.
     game.dungeon_level += 1;
-    game.map = make_map(player, characters, items, game.dungeon_level, tcod);
+    let (map, layout) = make_map(player, characters, items, &game.schedule, &game.item_table, &game.identity, game.dungeon_level, tcod);
+    game.map = map;
+    game.layout = layout;
     initialise_fov(tcod, &game.map);
+
+    // The stairs of a boss floor stay sealed until the boss has been defeated.
+    game.stairs_sealed = game.layout.has_arena();
+    if game.stairs_sealed {
+        game.messages.add(
+            "A heavy silence hangs over this floor. Something powerful lies in wait...",
+            LIGHT_RED,
+        );
+    }
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MapTheme {
     Fire, //Red
     Nature, //Green
@@ -133,6 +229,20 @@ fn two_greater(higher_one: u8, higher_two: u8, lower: u8) -> bool {
     return false
 }
 
+// Repeatedly generates colors, until they fit the given theme.
+// After enough failed attempts, the last set of colors is used regardless.
+fn gen_themed_colors(theme: MapTheme) -> [Color; 7] {
+    let max_tries = 200;
+    let mut colors = gen_colors();
+    for _ in 0..max_tries {
+        if set_map_theme(colors[GROUND_COLOR]) == theme {
+            break;
+        }
+        colors = gen_colors();
+    }
+    colors
+}
+
 fn set_map_theme(color: Color) -> MapTheme {
     let r = color.r;
     let b = color.b;
@@ -149,208 +259,29 @@ fn set_map_theme(color: Color) -> MapTheme {
     }
 }
 
-// Different map generation algorithms require different spawning systems, modifiers, corridors, etc...
-// The MapType enum allows for an easy way to keep track of all of this.
-enum MapType {
-    Rectangles,
-    DrunkenWalk,
-    CellularAutomata,
-    Maze,
-}
-
 pub fn make_map(
-    mut player: &mut Object,
-    mut characters: &mut Vec<Character>,
-    mut items: &mut HashMap<i32, Object>,
+    player: &mut Object,
+    characters: &mut Vec<Character>,
+    items: &mut HashMap<i32, Object>,
+    schedule: &LevelSchedule,
+    table: &ItemTable,
+    identity: &Identification,
     level: u32,
     tcod: &mut Tcod,
-) -> Map {
+) -> (Map, LevelLayout) {
+    // The schedule decides what this floor will look like.
     // Generate dungeon floor colors alongside variation
-    let colors = gen_colors();
-    let map_theme = set_map_theme(colors[GROUND_COLOR]);
-
-    // Fill map with wall tiles
-    let mut map = vec![vec![Tile::wall(&colors); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
-
-    // Creates vector to store rooms
-    // Another vector to store important points in non-room-based map gen.
-    let mut rects = vec![];
-    let mut points = vec![];
-
-    // Randomly decides which type of map to use, and generates it.
-//    let map_gen = rand::thread_rng().gen_range(1, 8);
-//    let map_gen = 1;
-
-    let mut map_chances = [
-        Weighted { // Weighting for basic rectangle room map gen.
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 1, value: 30, }, // Is the only option at the first floor.
-                    Transition { level: 3, value: 10, }, // Chance is lower from the 3rd floor onwards.
-                    Transition { level: 9, value: 0, }, // Becomes unavailable on the floor prior to the maze.
-                ],
-                level,
-            ),
-            item: 1,
-        },
-        Weighted { // Weighting for rectangle room gen with an open area in the middle.
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 3, value: 30, }, // Becomes available from the third floor
-                    Transition { level: 5, value: 10, }, // Chance lowered from the fifth floor.
-                    Transition { level: 9, value: 0, }, // Becomes unavailable on the floor prior to the maze.
-                ],
-                level,
-            ),
-            item: 2,
-        },
-        Weighted { // Weighting for rectangle drunken mining map gen.
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 4, value: 30, }, //  Becomes available from the fourth floor.
-                    Transition { level: 5, value: 10, }, // Chance lowered from the fifth floor onward.
-                    Transition { level: 7, value: 20, }, // Chances are raised to take into account the raising of another weight. (The next category)
-                    Transition { level: 9, value: 0, }, // Chance is zero to force the maze to appear for one floor.
-                    Transition { level: 10, value: 5, }, // A small chance remains for this to appear afterwards.
-                ],
-                level,
-            ),
-            item: 3,
-        },
-        Weighted { // Weighting for rectangle drunken mining map gen with an open area in the middle.
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 5, value: 30, }, //  Becomes available from the fifth floor.
-                    Transition { level: 7, value: 20, }, // Chance lowered from the seventh floor onward.
-                    Transition { level: 9, value: 0, }, // Chance is zero to force the maze to appear for one floor.
-                    Transition { level: 10, value: 5, }, // A small chance remains for this to appear afterwards.
-                ],
-                level,
-            ),
-            item: 4,
-        },
-        Weighted { // Weighting for the drunken walk map generation
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 9, value: 0, }, // Maze floor
-                    Transition { level: 10, value: 30, }, // On the tenth floor, it becomes available
-                ],
-                level,
-            ),
-            item: 5,
-        },
-        Weighted { // Weighting for the cellular automata map gen.
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 9, value: 0, }, // Maze floor
-                    Transition { level: 11, value: 15, }, // On the eleventh floor it becomes available at a lower rate than the drunken walk.
-                ],
-                level,
-            ),
-            item: 6,
-        },
-        Weighted { // Weighting for the maze map gen
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 9, value: 50, }, // Maze appears on the ninth floor.
-                    Transition { level: 10, value: 0, }, // Maze can't appear afterwards
-                ],
-                level,
-            ),
-            item: 7
-        },
-    ];
-    let map_gen = WeightedChoice::new(&mut map_chances);
-
-    let map_type = match map_gen.ind_sample(&mut rand::thread_rng()) {
-        // Standard rectangles map
-        1 => {
-            rectangles(&mut rects, &mut map, &colors, &mut player, tcod, RENDER);
-            MapType::Rectangles
-        },
-
-        // Rectangles map with the drunken miner modifier
-        2 => {
-            rectangles(&mut rects, &mut map, &colors, &mut player, tcod, RENDER);
-            caved_in(&mut map, &colors, tcod, RENDER);
-            MapType::Rectangles
-        },
-
-        // Rectangles map with an open area in the middle
-        3 => {
-            rectangles(&mut rects, &mut map, &colors, &mut player, tcod, RENDER);
-            mine_drunkenly(&rects, &mut map, &colors, tcod, RENDER);
-            MapType::Rectangles
-        },
-
-        // Rectangles map with the open area and drunken miner modifiers
-        4 => {
-            rectangles(&mut rects, &mut map, &colors, &mut player, tcod, RENDER);
-            mine_drunkenly(&rects, &mut map, &colors, tcod, RENDER);
-            caved_in(&mut map, &colors, tcod, RENDER);
-            MapType::Rectangles
-        },
-
-        // Creates a map entirely using the walking drunkard algorithm.
-        5 => {
-            drunk_walk(&mut points, &mut map, &colors, &mut player, tcod, RENDER);
-            MapType::DrunkenWalk
-        },
-
-        // Creates a map following the rules of cellular automata.
-        6 => {
-            cellular_automata(&mut map, &colors, &mut player, tcod, RENDER);
-            MapType::CellularAutomata
-        },
-
-        _ => {
-            maze(&mut map, &colors, &mut player, tcod, RENDER);
-            MapType::Maze
-        },
+    // If the schedule forces a theme, colors are generated until they match it.
+    let theme_override = schedule.theme_override(level);
+    let colors = match theme_override {
+        Some(theme) => gen_themed_colors(theme),
+        None => gen_colors(),
     };
+    let map_theme = theme_override.unwrap_or_else(|| set_map_theme(colors[GROUND_COLOR]));
 
-    // Adds in map modifiers, tunnels, and spawns stuff.
-    // Specifics of this is determined by the map type which was generated.
-    match map_type {
-        MapType::Rectangles => {
-            // Map modifiers
-            match rand::thread_rng().gen_range(1, 4) {
-                1 => pillars(&rects, &mut map, &colors, tcod, RENDER),
-                2 => rubble(&rects, &mut map, &colors, tcod, RENDER),
-                _ => {},
-            }
-
-            // Sorts the rooms.
-            room_sorter(&mut rects);
-
-            // Tunnels and spawns
-            create_tunnels(&mut rects, &mut map, &colors, tcod, RENDER);
-            rooms_spawner(&rects, &mut items, &map, &mut characters, level, map_theme);
-        },
-
-        MapType::DrunkenWalk => {
-            // Sorts the point vector.
-            room_sorter(&mut points);
-
-            joiner(&mut points, &mut map, &colors, tcod, RENDER); // Joins the different segments of the map together.
-            no_rooms_spawner(&mut items, &map, &mut characters, level, map_theme);
-        },
-
-        MapType::CellularAutomata => {
-            remove_inaccessible_tiles(&mut map, &player, &colors);
-            no_rooms_spawner(&mut items, &map, &mut characters, level, map_theme);
-        },
-
-        MapType::Maze => {
-            if rand::random() { // Opens some space up in the maze.
-                rectangles(&mut rects, &mut map, &colors, &mut player, tcod, RENDER); // Scatters some rooms throughout
-            } else {
-                caved_in(&mut map, &colors, tcod, RENDER); // Creates a large open chunk in the middle of the maze
-            }
-            maze_spawner(&mut items, &map, &mut characters, level, map_theme);
-        },
-    }
-
-    // Returns finished map.
-    map
+    // Decides which layout to use from the level schedule, and runs each of its stages.
+    let layout = schedule.choose_layout(level);
+    MapBuilder::new(colors, map_theme, level, player, characters, items, table, identity, tcod)
+        .stages(&layout.stages)
+        .build()
 }
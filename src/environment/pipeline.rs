@@ -0,0 +1,417 @@
+use crate::Tcod;
+use crate::environment::{ Map, MapTheme, RENDER, MAP_WIDTH, MAP_HEIGHT };
+use crate::environment::map::tiles::Tile;
+use crate::environment::layout::LevelLayout;
+use crate::environment::map::{ // List of map gen variants go here
+    Rect,
+    room_sorter,
+    create_tunnels,
+    joiner,
+    rectangles::rectangles,
+    drunk_walk::drunk_walk,
+    cellular_automata::cellular_automata,
+    maze::maze,
+    arena::arena,
+    modifiers::*,
+    rooms::*,
+};
+use crate::environment::spawner::{ rooms_spawner, no_rooms_spawner, boss_spawner, create_stairs };
+use crate::environment::spawner::character_spawns::assign_idle_ai;
+use crate::objects::items::table::ItemTable;
+use crate::objects::items::identify::Identification;
+use crate::objects::{ Object, Character };
+use crate::pathing::remove_inaccessible_tiles;
+
+use std::collections::HashMap;
+
+use rand::*;
+
+use serde::{ Serialize, Deserialize };
+
+use tcod::colors::*;
+
+// Base generators carve out the shape of the map.
+// Running more than one of them draws each on top of the last.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Generator {
+    Rectangles,
+    DrunkenWalk,
+    CellularAutomata,
+    Maze,
+    Arena,
+}
+
+// Modifiers change a map after it has been generated.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Modifier {
+    CavedIn,
+    MineDrunkenly,
+    Pillars,
+    Rubble,
+    Vaults,
+}
+
+// Connectors join the separate parts of a map together.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Connector {
+    Rooms, // Tunnels between rooms, which are also given their roles.
+    Joiner, // Tunnels between the points left behind by a drunken walk.
+}
+
+// Validators make sure that a map is playable.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Validator {
+    RemoveInaccessible,
+}
+
+// Decides where the stairs are placed.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StairPlacer {
+    LastRoom,
+    RandomTile,
+    Corner,
+    ArenaEnd,
+}
+
+// Fills the map with monsters, items and traps.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Spawner {
+    Rooms,
+    NoRooms,
+    Boss,
+}
+
+// A single step of map generation.
+// OneOf picks one of the listed chains of stages at random, an empty chain does nothing.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Stage {
+    Base(Generator),
+    Modifier(Modifier),
+    Connector(Connector),
+    Validator(Validator),
+    Stairs(StairPlacer),
+    Spawner(Spawner),
+    OneOf(Vec<Vec<Stage>>),
+}
+
+// Builds a map by running stages one after another.
+// Rects and points are left behind by the base generators, for later stages to make use of.
+pub struct MapBuilder<'a> {
+    map: Map,
+    colors: [Color; 7],
+    theme: MapTheme,
+    level: u32,
+    rects: Vec<Rect>,
+    points: Vec<(i32, i32)>,
+    rooms: Vec<Room>,
+    stairs: Option<(i32, i32)>,
+    player: &'a mut Object,
+    characters: &'a mut Vec<Character>,
+    items: &'a mut HashMap<i32, Object>,
+    table: &'a ItemTable,
+    identity: &'a Identification,
+    tcod: &'a mut Tcod,
+}
+
+impl<'a> MapBuilder<'a> {
+    // Starts off with a map full of walls, and no entities.
+    pub fn new(
+        colors: [Color; 7],
+        theme: MapTheme,
+        level: u32,
+        player: &'a mut Object,
+        characters: &'a mut Vec<Character>,
+        items: &'a mut HashMap<i32, Object>,
+        table: &'a ItemTable,
+        identity: &'a Identification,
+        tcod: &'a mut Tcod,
+    ) -> MapBuilder<'a> {
+        // Ensures that there are no existing entities in the character, or item collections.
+        characters.clear();
+        items.clear();
+
+        MapBuilder {
+            map: vec![vec![Tile::wall(&colors); MAP_HEIGHT as usize]; MAP_WIDTH as usize],
+            colors: colors,
+            theme: theme,
+            level: level,
+            rects: vec![],
+            points: vec![],
+            rooms: vec![],
+            stairs: None,
+            player: player,
+            characters: characters,
+            items: items,
+            table: table,
+            identity: identity,
+            tcod: tcod,
+        }
+    }
+
+    // Runs a list of stages in order.
+    pub fn stages(mut self, stages: &[Stage]) -> MapBuilder<'a> {
+        for stage in stages {
+            self = self.stage(stage);
+        }
+        self
+    }
+
+    // Runs a single stage.
+    pub fn stage(mut self, stage: &Stage) -> MapBuilder<'a> {
+        match stage {
+            Stage::Base(generator) => self.base(*generator),
+            Stage::Modifier(modifier) => self.modifier(*modifier),
+            Stage::Connector(connector) => self.connector(*connector),
+            Stage::Validator(Validator::RemoveInaccessible) => {
+                remove_inaccessible_tiles(&mut self.map, &self.player, &self.colors);
+            },
+            Stage::Stairs(placer) => self.stairs(*placer),
+            Stage::Spawner(spawner) => self.spawner(*spawner),
+            Stage::OneOf(choices) => {
+                if !choices.is_empty() {
+                    let choice = rand::thread_rng().gen_range(0, choices.len());
+                    return self.stages(&choices[choice]);
+                }
+            },
+        }
+        self
+    }
+
+    // Returns the finished map, alongside a record of its layout.
+    // A map must always have a way down, so stairs are placed on a random tile if no stage placed them.
+    pub fn build(mut self) -> (Map, LevelLayout) {
+        if self.stairs.is_none() {
+            self.stairs(StairPlacer::RandomTile);
+        }
+
+        // Rects which were never given a role, such as those scattered through a maze, are kept as ordinary rooms.
+        for rect in &self.rects {
+            if !self.rooms.iter().any(|room| room.rect == *rect) {
+                self.rooms.push(Room { rect: *rect, role: RoomRole::Ordinary });
+            }
+        }
+
+        let stairs = self.stairs.unwrap_or(self.player.pos());
+        let layout = LevelLayout::new(&self.map, self.rooms, self.points, self.player.pos(), stairs);
+
+        // Monsters are given their idle behaviour last, as patrols follow the finished layout.
+        assign_idle_ai(self.characters, &layout);
+        (self.map, layout)
+    }
+
+    fn base(&mut self, generator: Generator) {
+        let (map, colors, tcod) = (&mut self.map, &self.colors, &mut *self.tcod);
+        match generator {
+            Generator::Rectangles => rectangles(&mut self.rects, map, colors, self.player, tcod, RENDER),
+            Generator::DrunkenWalk => drunk_walk(&mut self.points, map, colors, self.player, tcod, RENDER),
+            Generator::CellularAutomata => cellular_automata(map, colors, self.player, tcod, RENDER),
+            Generator::Maze => maze(map, colors, self.player, tcod, RENDER),
+            Generator::Arena => {
+                arena(&mut self.rects, map, colors, self.player, tcod, RENDER);
+
+                // The antechamber is a safe place to prepare, before entering the arena.
+                self.rooms = vec![
+                    Room { rect: self.rects[0], role: RoomRole::Ordinary },
+                    Room { rect: self.rects[1], role: RoomRole::Arena },
+                ];
+            },
+        }
+    }
+
+    fn modifier(&mut self, modifier: Modifier) {
+        let (map, colors, tcod) = (&mut self.map, &self.colors, &mut *self.tcod);
+        match modifier {
+            Modifier::CavedIn => caved_in(map, colors, tcod, RENDER),
+            Modifier::MineDrunkenly => mine_drunkenly(&self.rects, map, colors, tcod, RENDER),
+
+            // Pillars are placed inside rooms when there are enough of them, otherwise they're scattered about.
+            Modifier::Pillars => {
+                if self.rects.len() >= 2 {
+                    pillars(&self.rects, map, colors, tcod, RENDER);
+                } else {
+                    scatter_pillars(map, colors, tcod, RENDER);
+                }
+            },
+            Modifier::Rubble => {
+                if self.rects.len() >= 2 {
+                    rubble(&self.rects, map, colors, tcod, RENDER);
+                }
+            },
+
+            // Vaults are kept track of as treasure rooms, so they're filled with loot later on.
+            Modifier::Vaults => {
+                let mut new_vaults = vec![];
+                vaults(&mut new_vaults, &self.rects, self.player, map, colors, tcod, RENDER);
+                for vault in new_vaults {
+                    self.rooms.push(Room { rect: vault, role: RoomRole::Treasure });
+                }
+            },
+        }
+    }
+
+    fn connector(&mut self, connector: Connector) {
+        let (map, colors, tcod) = (&mut self.map, &self.colors, &mut *self.tcod);
+        match connector {
+            Connector::Rooms => {
+                if self.rects.is_empty() {
+                    return;
+                }
+
+                // Sorts the rooms, and decides what each of them will be used for.
+                room_sorter(&mut self.rects);
+                let mut rooms = assign_room_roles(&self.rects, self.player.pos());
+
+                // Treasure rooms are kept off the main path, and can only be reached through secret tunnels.
+                // Any a tunnel happened to pass through are no longer hidden, so they lose their treasure.
+                let mut connected = connected_rects(&rooms);
+                create_tunnels(&mut connected, map, colors, tcod, RENDER);
+                expose_crossed_treasure_rooms(&mut rooms, map);
+
+                // Any vaults which were already stamped onto the map are kept.
+                rooms.extend(self.rooms.iter().filter(|room| room.role == RoomRole::Treasure));
+                self.rooms = rooms;
+                hide_treasure_rooms(&self.rooms, map, colors, tcod, RENDER);
+            },
+
+            Connector::Joiner => {
+                room_sorter(&mut self.points);
+                joiner(&mut self.points, map, colors, tcod, RENDER);
+            },
+        }
+    }
+
+    fn stairs(&mut self, placer: StairPlacer) {
+        // Stairs are placed before anything is spawned, so that nothing can be spawned on top of them.
+        let (x, y) = stair_position(placer, &self.map, &self.rooms, self.items, self.player.pos());
+        create_stairs(self.items, x, y);
+        self.stairs = Some((x, y));
+    }
+
+    fn spawner(&mut self, spawner: Spawner) {
+        let (map, items, characters) = (&self.map, &mut *self.items, &mut *self.characters);
+        match spawner {
+            Spawner::Rooms => rooms_spawner(&self.rooms, items, map, characters, self.player.pos(), self.table, self.identity, self.level, self.theme),
+            Spawner::NoRooms => no_rooms_spawner(&self.rooms, items, map, characters, self.table, self.identity, self.level, self.theme),
+            Spawner::Boss => boss_spawner(&self.rooms, items, map, characters, self.table, self.identity, self.level, self.theme),
+        }
+    }
+}
+
+// Works out where the stairs go on a floor.
+fn stair_position(
+    placer: StairPlacer,
+    map: &Map,
+    rooms: &[Room],
+    items: &HashMap<i32, Object>,
+    player: (i32, i32),
+) -> (i32, i32) {
+    let position = match placer {
+        StairPlacer::LastRoom => rooms
+            .iter()
+            .find(|room| room.role == RoomRole::Stairs)
+            .map(|room| room.rect.center()),
+
+        // The stairs are placed at the far end of the arena, with the boss standing between them and the player.
+        StairPlacer::ArenaEnd => rooms
+            .iter()
+            .find(|room| room.role == RoomRole::Arena)
+            .map(|room| (room.rect.x2 - 2, room.rect.center().1)),
+
+        StairPlacer::Corner => {
+            let corners = [
+                (1, 1),
+                (1, MAP_HEIGHT - 2),
+                (MAP_WIDTH - 2, 1),
+                (MAP_WIDTH - 2, MAP_HEIGHT - 2),
+            ];
+            let open: Vec<&(i32, i32)> = corners
+                .iter()
+                .filter(|(x, y)| map[*x as usize][*y as usize].empty)
+                .collect();
+            if open.is_empty() {
+                None
+            } else {
+                Some(*open[rand::thread_rng().gen_range(0, open.len())])
+            }
+        },
+
+        StairPlacer::RandomTile => None,
+    };
+
+    // Any placer which can't find a spot falls back to a random tile.
+    match position {
+        Some(position) => position,
+        None => loop {
+            let x = rand::thread_rng().gen_range(1, MAP_WIDTH - 1);
+            let y = rand::thread_rng().gen_range(1, MAP_HEIGHT - 1);
+
+            if map[x as usize][y as usize].empty &&
+            Object::trap_at(x, y, items).is_none() &&
+            player != (x, y) {
+                break (x, y);
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::map::create_room;
+    use crate::objects::traps::TrapKind;
+
+    const COLORS: [Color; 7] = [GREY, DARK_SEPIA, SEPIA, LIGHT_SEPIA, DARKER_SEPIA, DARK_GREY, DARKEST_GREY];
+
+    // A solid map with a single four tile room, from (11, 11) to (12, 12).
+    fn tiny_map() -> Map {
+        let mut map = vec![vec![Tile::wall(&COLORS); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+        create_room(Rect::new(10, 10, 3, 3), &mut map, &COLORS);
+        map
+    }
+
+    // With the player, and a trap, taking up half of the room, only two tiles are left for the stairs.
+    fn assert_falls_back(placer: StairPlacer, rooms: &[Room]) {
+        let map = tiny_map();
+        let mut items = HashMap::new();
+        items.insert(0, Object::new_trap(11, 12, TrapKind::Pit, 1, MapTheme::Earth));
+
+        for _ in 0..50 {
+            let position = stair_position(placer, &map, rooms, &items, (11, 11));
+            assert!(position == (12, 11) || position == (12, 12), "stairs placed at {:?}", position);
+        }
+    }
+
+    #[test]
+    fn last_room_without_a_stairs_room_falls_back() {
+        let rooms = [Room { rect: Rect::new(10, 10, 3, 3), role: RoomRole::Ordinary }];
+        assert_falls_back(StairPlacer::LastRoom, &rooms);
+    }
+
+    #[test]
+    fn arena_end_without_an_arena_falls_back() {
+        assert_falls_back(StairPlacer::ArenaEnd, &[]);
+    }
+
+    #[test]
+    fn corner_with_no_open_corners_falls_back() {
+        assert_falls_back(StairPlacer::Corner, &[]);
+    }
+
+    #[test]
+    fn random_tile_avoids_the_player_and_traps() {
+        assert_falls_back(StairPlacer::RandomTile, &[]);
+    }
+
+    #[test]
+    fn placers_use_their_room_when_there_is_one() {
+        let map = tiny_map();
+        let items = HashMap::new();
+        let rooms = [
+            Room { rect: Rect::new(10, 10, 3, 3), role: RoomRole::Arena },
+            Room { rect: Rect::new(30, 20, 6, 6), role: RoomRole::Stairs },
+        ];
+
+        assert_eq!(stair_position(StairPlacer::ArenaEnd, &map, &rooms, &items, (11, 11)), (11, 11));
+        assert_eq!(stair_position(StairPlacer::LastRoom, &map, &rooms, &items, (11, 11)), (33, 23));
+    }
+}
@@ -0,0 +1,172 @@
+use crate::environment::MapTheme;
+use crate::environment::pipeline::Stage;
+use crate::environment::spawner::{ Transition, from_dungeon_level };
+use crate::graphics::gui::Messages;
+
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+
+use rand::distributions::{ IndependentSample, Weighted, WeightedChoice };
+
+use serde::{ Serialize, Deserialize };
+
+use tcod::colors::*;
+
+// The schedule is read from this file at the start of each game, so it can be tweaked without recompiling.
+const SCHEDULE_FILE: &str = "level_schedule.json";
+
+// A copy of the schedule is built into the game, in case the file is missing or broken.
+const DEFAULT_SCHEDULE: &str = include_str!("../../level_schedule.json");
+
+// A chain of generation stages, alongside how likely it is to appear at each depth.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Layout {
+    pub name: String,
+    pub stages: Vec<Stage>,
+    pub weights: Vec<Transition>,
+}
+
+// Forces a layout to appear on a floor.
+// If repeat is above zero, it appears again every time that many floors have passed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForcedFloor {
+    pub level: u32,
+    pub repeat: u32,
+    pub layout: String,
+}
+
+// Forces the theme of a floor, following the same rules as forced floors.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThemeOverride {
+    pub level: u32,
+    pub repeat: u32,
+    pub theme: MapTheme,
+}
+
+// Schedule definition.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LevelSchedule {
+    pub layouts: Vec<Layout>,
+    pub forced: Vec<ForcedFloor>,
+    pub themes: Vec<ThemeOverride>,
+}
+
+// Checks to see if a floor is covered by a starting level, and a repeat interval.
+fn applies_to(level: u32, start: u32, repeat: u32) -> bool {
+    level == start || (repeat > 0 && level > start && (level - start) % repeat == 0)
+}
+
+impl LevelSchedule {
+    // Loads the schedule from the schedule file.
+    // If it can't be read, the player is told why, and the built in schedule is used instead.
+    pub fn load(messages: &mut Messages) -> LevelSchedule {
+        match LevelSchedule::from_file() {
+            Ok(schedule) => schedule,
+            Err(e) => {
+                messages.add(format!("Could not load {}, using the default schedule: {}", SCHEDULE_FILE, e), ORANGE);
+                LevelSchedule::built_in()
+            },
+        }
+    }
+
+    // The schedule built into the game.
+    pub fn built_in() -> LevelSchedule {
+        serde_json::from_str(DEFAULT_SCHEDULE).expect("Default level schedule is invalid")
+    }
+
+    fn from_file() -> Result<LevelSchedule, Box<dyn Error>> {
+        let mut json_schedule = String::new();
+        let mut file = File::open(SCHEDULE_FILE)?;
+        file.read_to_string(&mut json_schedule)?;
+        let schedule = serde_json::from_str::<LevelSchedule>(&json_schedule)?;
+        schedule.validate()?;
+        Ok(schedule)
+    }
+
+    // Checks that the schedule can always choose a layout.
+    // There has to be at least one layout, and every forced floor has to name one of them.
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.layouts.is_empty() {
+            return Err("the schedule has no layouts".into());
+        }
+        for forced in self.forced.iter() {
+            if !self.layouts.iter().any(|layout| layout.name == forced.layout) {
+                return Err(format!("level {} is forced to use unknown layout \"{}\"", forced.level, forced.layout).into());
+            }
+        }
+        Ok(())
+    }
+
+    // Picks the layout to use for a floor.
+    // Forced floors take priority, otherwise a layout is chosen using the weights for that depth.
+    pub fn choose_layout(&self, level: u32) -> &Layout {
+        let forced = self.forced
+            .iter()
+            .find(|forced| applies_to(level, forced.level, forced.repeat))
+            .and_then(|forced| self.layouts.iter().find(|layout| layout.name == forced.layout));
+        if let Some(layout) = forced {
+            return layout;
+        }
+
+        let mut layout_chances: Vec<Weighted<usize>> = self.layouts
+            .iter()
+            .enumerate()
+            .map(|(id, layout)| Weighted {
+                weight: from_dungeon_level(&layout.weights, level),
+                item: id,
+            })
+            .collect();
+
+        // If nothing is available at this depth, the first layout is used.
+        if layout_chances.iter().all(|weighted| weighted.weight == 0) {
+            return &self.layouts[0];
+        }
+
+        let layout_choice = WeightedChoice::new(&mut layout_chances);
+        &self.layouts[layout_choice.ind_sample(&mut rand::thread_rng())]
+    }
+
+    // Returns the theme a floor is forced to use, if there is one.
+    pub fn theme_override(&self, level: u32) -> Option<MapTheme> {
+        self.themes
+            .iter()
+            .find(|themed| applies_to(level, themed.level, themed.repeat))
+            .map(|themed| themed.theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_schedule_is_valid() {
+        let schedule: LevelSchedule = serde_json::from_str(DEFAULT_SCHEDULE).unwrap();
+        assert!(schedule.validate().is_ok());
+    }
+
+    #[test]
+    fn schedule_without_layouts_is_rejected() {
+        let schedule: LevelSchedule = serde_json::from_str(r#"{ "layouts": [], "forced": [], "themes": [] }"#).unwrap();
+        assert!(schedule.validate().is_err());
+    }
+
+    #[test]
+    fn forced_floor_with_unknown_layout_is_rejected() {
+        let schedule: LevelSchedule = serde_json::from_str(r#"{
+            "layouts": [{ "name": "caves", "stages": [], "weights": [] }],
+            "forced": [{ "level": 5, "repeat": 0, "layout": "missing" }],
+            "themes": []
+        }"#).unwrap();
+        assert!(schedule.validate().is_err());
+    }
+
+    #[test]
+    fn forced_floors_repeat() {
+        assert!(applies_to(5, 5, 0));
+        assert!(!applies_to(10, 5, 0));
+        assert!(applies_to(15, 5, 5));
+        assert!(!applies_to(3, 5, 5));
+    }
+}
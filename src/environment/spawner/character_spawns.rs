@@ -1,7 +1,11 @@
 use crate::environment::{ Map, MapTheme };
 use crate::environment::map::Rect;
+use crate::environment::map::rooms::RoomRole;
+use crate::environment::layout::LevelLayout;
 use crate::objects::{ Object, Character };
-use crate::objects::npc::enemies::{ generate_monster, monster_level_up };
+use crate::objects::npc::enemies::{ generate_monster, generate_family_monster, monster_level_up };
+use crate::objects::npc::enemies::traits::get_trait_family;
+use crate::objects::npc::ai::{ Ai, Idle };
 use super::*;
 
 use rand::*;
@@ -78,7 +82,7 @@ fn monster_strength_weighting(level: u32) -> [Weighted<&'static str>; 3] {
     ]
 }
 
-pub fn room_characters(room: Rect, map: &Map, characters: &mut Vec<Character>, level: u32, theme: MapTheme) {
+pub fn room_characters(room: Rect, map: &Map, characters: &mut Vec<Character>, table: &ItemTable, identity: &Identification, level: u32, theme: MapTheme) {
     // Creates maximum number of monsters per room.
     let max_monsters = from_dungeon_level(
         &[
@@ -103,9 +107,9 @@ pub fn room_characters(room: Rect, map: &Map, characters: &mut Vec<Character>, l
 
         if !Object::is_blocked(x, y, map, characters) {
             let mut monster = match monster_choice.ind_sample(&mut rand::thread_rng()) {
-                "weak_monster" => generate_monster(x, y, 1, level, theme),
-                "medium_monster" => generate_monster(x, y, 2, level, theme),
-                "powerful_monster" => generate_monster(x, y, 3, level, theme),
+                "weak_monster" => generate_monster(x, y, 1, level, theme, table, identity),
+                "medium_monster" => generate_monster(x, y, 2, level, theme, table, identity),
+                "powerful_monster" => generate_monster(x, y, 3, level, theme, table, identity),
                 _ => unreachable!(),
             };
             monster.object.alive = true;
@@ -122,7 +126,81 @@ pub fn room_characters(room: Rect, map: &Map, characters: &mut Vec<Character>, l
     }
 }
 
-pub fn no_room_characters(map: &Map, characters: &mut Vec<Character>, level: u32, theme: MapTheme) {
+// Fills a room with a pack of monsters which all share the same trait family.
+pub fn den_characters(room: Rect, map: &Map, characters: &mut Vec<Character>, table: &ItemTable, identity: &Identification, level: u32, theme: MapTheme) {
+    // Dens hold more monsters than a regular room.
+    let pack_size = from_dungeon_level(
+        &[
+            Transition { level: 1, value: 3 },
+            Transition { level: 4, value: 4 },
+            Transition { level: 6, value: 6 },
+        ],
+        level,
+    );
+
+    let mut monster_chances = monster_strength_weighting(level);
+    let monster_choice = WeightedChoice::new(&mut monster_chances);
+
+    // The whole pack is drawn from a single family.
+    let family = get_trait_family(theme);
+
+    for _ in 0..pack_size {
+
+        // Choose random spot for the monster
+        let x = rand::thread_rng().gen_range(room.x1 + 1, room.x2);
+        let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
+
+        if !Object::is_blocked(x, y, map, characters) {
+            let mut monster = match monster_choice.ind_sample(&mut rand::thread_rng()) {
+                "weak_monster" => generate_family_monster(x, y, 1, level, family, table, identity),
+                "medium_monster" => generate_family_monster(x, y, 2, level, family, table, identity),
+                "powerful_monster" => generate_family_monster(x, y, 3, level, family, table, identity),
+                _ => unreachable!(),
+            };
+            monster.object.alive = true;
+
+            // Level up the monster to increase the difficulty.
+            let mut level_up = level - 1;
+            while level_up > 0 {
+                monster.object.fighter.as_mut().map(|mut f| monster_level_up(&mut f));
+                level_up -= 1;
+            }
+
+            characters.push(monster);
+        }
+    }
+}
+
+// Places a single powerful monster beside the stairs, to guard them.
+pub fn stairs_guardian(room: Rect, map: &Map, characters: &mut Vec<Character>, table: &ItemTable, identity: &Identification, level: u32, theme: MapTheme) {
+    // Guardians are always a tier stronger than the weakest monsters on the floor.
+    let tier = if level >= 6 { 3 } else { 2 };
+
+    // Tries each tile around the stairs until a free one is found.
+    let (center_x, center_y) = room.center();
+    let free_tile = [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)]
+        .iter()
+        .map(|(dx, dy)| (center_x + dx, center_y + dy))
+        .find(|&(x, y)| room.contains(x, y) && !Object::is_blocked(x, y, map, characters));
+
+    if let Some((x, y)) = free_tile {
+        let mut guardian = generate_monster(x, y, tier, level, theme, table, identity);
+        guardian.object.name = format!("{} guardian", guardian.object.name);
+        guardian.object.alive = true;
+        guardian.object.fighter.as_mut().map(|f| f.idle = Idle::Guard);
+
+        // Guardians are levelled up a little further than other monsters.
+        let mut level_up = level + 1;
+        while level_up > 0 {
+            guardian.object.fighter.as_mut().map(|mut f| monster_level_up(&mut f));
+            level_up -= 1;
+        }
+
+        characters.push(guardian);
+    }
+}
+
+pub fn no_room_characters(map: &Map, characters: &mut Vec<Character>, table: &ItemTable, identity: &Identification, level: u32, theme: MapTheme) {
 
     // Creates maximum number of monsters per room.
     let max_monsters = from_dungeon_level(
@@ -158,9 +236,9 @@ pub fn no_room_characters(map: &Map, characters: &mut Vec<Character>, level: u32
 
             if !Object::is_blocked(x, y, map, characters) {
                 let mut monster = match monster_choice.ind_sample(&mut rand::thread_rng()) {
-                    "weak_monster" => generate_monster(x, y, 1, level, theme),
-                    "medium_monster" => generate_monster(x, y, 2, level, theme),
-                    "powerful_monster" => generate_monster(x, y, 3, level, theme),
+                    "weak_monster" => generate_monster(x, y, 1, level, theme, table, identity),
+                    "medium_monster" => generate_monster(x, y, 2, level, theme, table, identity),
+                    "powerful_monster" => generate_monster(x, y, 3, level, theme, table, identity),
                     _ => unreachable!(),
                 };
                 monster.object.alive = true;
@@ -185,3 +263,46 @@ pub fn no_room_characters(map: &Map, characters: &mut Vec<Character>, level: u32
         map_region_start += 10;
     }
 }
+
+// Decides what each monster does before it has spotted the player, based on its idle behavior.
+// Guardians stay at their post, and monsters which already have a special AI are left alone.
+pub fn assign_idle_ai(characters: &mut [Character], layout: &LevelLayout) {
+    for character in characters.iter_mut() {
+        let is_basic = match character.object.ai {
+            Some(Ai::Basic) => true,
+            _ => false,
+        };
+        if !is_basic {
+            continue;
+        }
+
+        let idle_ai = match character.object.fighter.map(|f| f.idle) {
+            Some(Idle::Sleep) => Ai::Sleeping,
+            Some(Idle::Wander) => Ai::Wandering { destination: None },
+            Some(Idle::Patrol) => match patrol_route(layout) {
+                Some(route) => Ai::Patrol { route: route, next: 0 },
+                None => Ai::Wandering { destination: None },
+            },
+            Some(Idle::Still) | Some(Idle::Guard) | None => Ai::Basic,
+        };
+        character.object.ai = Some(idle_ai);
+    }
+}
+
+// Picks a few room centers for a monster to patrol between.
+// Hidden rooms are left out, as patrols can't search for secret passages.
+fn patrol_route(layout: &LevelLayout) -> Option<Vec<(i32, i32)>> {
+    let mut centers: Vec<(i32, i32)> = layout.rooms
+        .iter()
+        .filter(|room| room.role != RoomRole::Treasure)
+        .map(|room| room.rect.center())
+        .collect();
+    if centers.len() < 2 {
+        return None;
+    }
+
+    let stops = rand::thread_rng().gen_range(2, centers.len().min(4) + 1);
+    rand::thread_rng().shuffle(&mut centers);
+    centers.truncate(stops);
+    Some(centers)
+}
@@ -1,6 +1,11 @@
 use crate::environment::Map;
 use crate::environment::map::Rect;
-use crate::objects::{ Object, Character, items::Item };
+use crate::CARRY_CHANCE;
+use crate::objects::{ Object, Character };
+use crate::objects::items::Category;
+use crate::objects::items::table::ItemTable;
+use crate::objects::items::identify::Identification;
+use crate::objects::items::monsters::MONSTER_INVENTORY_SIZE;
 use super::*;
 
 use std::collections::HashMap;
@@ -8,88 +13,14 @@ use std::collections::HashMap;
 use rand::*;
 use rand::distributions::{ IndependentSample, Weighted, WeightedChoice };
 
-fn item_weighting(level: u32) -> [Weighted<Item>; 8] {
-    [
-        Weighted {
-            weight: 35,
-            item: Item::Heal,
-        },
-        Weighted {
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 4, value: 25, }
-                ],
-                level,
-            ),
-            item: Item::LightningBoltScroll,
-        },
-        Weighted {
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 6, value: 25, },
-                    Transition { level: 8, value: 50, },
-                    Transition { level: 10, value: 10, },
-                ],
-                level,
-            ),
-            item: Item::FireballScroll,
-        },
-        Weighted {
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 2, value: 10, },
-                    Transition { level: 12, value: 20, },
-                ],
-                level,
-            ),
-            item: Item::ConfusionScroll,
-        },
-        Weighted {
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 4, value: 10, },
-                    Transition { level: 16, value: 20, },
-                ],
-                level,
-            ),
-            item: Item::FearScroll,
-        },
-        Weighted {
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 5, value: 10, },
-                ],
-                level,
-            ),
-            item: Item::HpUp,
-        },
-        Weighted {
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 7, value: 10, },
-                ],
-                level,
-            ),
-            item: Item::PowUp,
-        },
-        Weighted {
-            weight: from_dungeon_level(
-                &[
-                    Transition { level: 10, value: 10, },
-                ],
-                level,
-            ),
-            item: Item::DefUp,
-        },
-    ]
-}
-
 pub fn room_items(
     room: Rect,
     items: &mut HashMap<i32, Object>,
     map: &Map,
     characters: &mut Vec<Character>,
     item_counter: &mut i32,
+    table: &ItemTable,
+    identity: &Identification,
     level: u32
 ) {
     // Decides maximum number of items per room.
@@ -101,7 +32,7 @@ pub fn room_items(
         level,
     );
 
-    let mut item_chances = item_weighting(level);
+    let mut item_chances = table.weights(level);
     let item_choice = WeightedChoice::new(&mut item_chances);
 
     // Choose random number of items.
@@ -113,51 +44,130 @@ pub fn room_items(
         let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
 
         if !Object::is_blocked(x, y, map, characters) {
-            let item = match item_choice.ind_sample(&mut rand::thread_rng()) {
-                Item::Heal => {
-                    // Create a health potion.
-                    Object::health_pot(x, y)
-                },
-                Item::LightningBoltScroll => {
-                    // Creates a lightning bolt scroll.
-                    Object::lightning_bolt_scroll(x, y)
-                },
-                Item::FireballScroll => {
-                    // Creates a fireball scroll
-                    Object::fireball_scroll(x, y)
-                },
-                Item::ConfusionScroll => {
-                    // Creates a confusion scroll
-                    Object::confusion_scroll(x, y)
-                },
-                Item::FearScroll => {
-                    // Creates a confusion scroll
-                    Object::fear_scroll(x, y)
-                },
-                Item::HpUp => {
-                    // Creates a Health upgrade
-                    Object::health_up(x, y)
-                },
-                Item::PowUp => {
-                    // Creates a Power upgrade
-                    Object::power_up(x, y)
-                },
-                Item::DefUp => {
-                    // Creates a Defense upgrade
-                    Object::defense_up(x, y)
-                },
-            };
+            let item = table.items[item_choice.ind_sample(&mut rand::thread_rng())].create(x, y, level, identity);
             items.insert(*item_counter, item);
             *item_counter += 1;
         }
     }
 }
 
+// Treasure rooms are packed with items, but have no monsters of their own.
+pub fn treasure_items(
+    room: Rect,
+    items: &mut HashMap<i32, Object>,
+    map: &Map,
+    characters: &mut Vec<Character>,
+    item_counter: &mut i32,
+    table: &ItemTable,
+    identity: &Identification,
+    level: u32
+) {
+    // Decides how many items make up the treasure.
+    let num_items = from_dungeon_level(
+        &[
+            Transition { level: 1, value: 3 },
+            Transition { level: 4, value: 4 },
+            Transition { level: 10, value: 6 },
+        ],
+        level,
+    );
+
+    let mut item_chances = table.weights(level);
+    let item_choice = WeightedChoice::new(&mut item_chances);
+
+    spawn_room_items(room, items, map, characters, item_counter, num_items, table, identity, &item_choice, level);
+}
+
+// Libraries only contain scrolls.
+pub fn library_items(
+    room: Rect,
+    items: &mut HashMap<i32, Object>,
+    map: &Map,
+    characters: &mut Vec<Character>,
+    item_counter: &mut i32,
+    table: &ItemTable,
+    identity: &Identification,
+    level: u32
+) {
+    // Decides maximum number of scrolls in the library.
+    let max_items = from_dungeon_level(
+        &[
+            Transition { level: 1, value: 2 },
+            Transition { level: 6, value: 3 },
+        ],
+        level,
+    );
+    let num_items = rand::thread_rng().gen_range(1, max_items + 1);
+
+    // Uses the regular item weighting, with every non-scroll item removed.
+    // Scrolls which haven't started appearing yet are left out, unless there are none at all.
+    let mut scroll_chances: Vec<Weighted<usize>> = table.weights(level)
+        .into_iter()
+        .filter(|weighted| table.items[weighted.item].item.category == Category::Scroll && weighted.weight > 0)
+        .map(|weighted| Weighted {
+            weight: weighted.weight.max(10),
+            item: weighted.item,
+        })
+        .collect();
+    if scroll_chances.is_empty() {
+        match table.items.iter().position(|def| def.item.category == Category::Scroll) {
+            Some(index) => scroll_chances.push(Weighted { weight: 10, item: index }),
+            None => return,
+        }
+    }
+    let item_choice = WeightedChoice::new(&mut scroll_chances);
+
+    spawn_room_items(room, items, map, characters, item_counter, num_items, table, identity, &item_choice, level);
+}
+
+// Places the given number of items in free tiles of a room.
+fn spawn_room_items(
+    room: Rect,
+    items: &mut HashMap<i32, Object>,
+    map: &Map,
+    characters: &mut Vec<Character>,
+    item_counter: &mut i32,
+    num_items: u32,
+    table: &ItemTable,
+    identity: &Identification,
+    item_choice: &WeightedChoice<usize>,
+    level: u32,
+) {
+    for _ in 0..num_items {
+        // Select random spot for the item.
+        let x = rand::thread_rng().gen_range(room.x1 + 1, room.x2);
+        let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
+
+        // Items aren't stacked, or placed on the stairs.
+        let occupied = items.values().any(|item| item.pos() == (x, y));
+        if !Object::is_blocked(x, y, map, characters) && !occupied {
+            let item = table.items[item_choice.ind_sample(&mut rand::thread_rng())].create(x, y, level, identity);
+            items.insert(*item_counter, item);
+            *item_counter += 1;
+        }
+    }
+}
+
+// Rolls for the items a newly spawned monster carries, which it drops when slain.
+pub fn carried_items(table: &ItemTable, identity: &Identification, level: u32) -> Vec<Object> {
+    let mut item_chances = table.weights(level);
+    let item_choice = WeightedChoice::new(&mut item_chances);
+
+    let mut carried = vec![];
+    while carried.len() < MONSTER_INVENTORY_SIZE && rand::thread_rng().gen::<f32>() < CARRY_CHANCE {
+        // Carried items are placed wherever the monster falls.
+        carried.push(table.items[item_choice.ind_sample(&mut rand::thread_rng())].create(0, 0, level, identity));
+    }
+    carried
+}
+
 pub fn no_room_items(
     items: &mut HashMap<i32, Object>,
     map: &Map,
     characters: &mut Vec<Character>,
     item_counter: &mut i32,
+    table: &ItemTable,
+    identity: &Identification,
     level: u32
 ) {
     // Decides maximum number of items per room.
@@ -172,7 +182,7 @@ pub fn no_room_items(
     // Choose random number of items.
     let num_items = rand::thread_rng().gen_range(0, max_items + 1);
 
-    let mut item_chances = item_weighting(level);
+    let mut item_chances = table.weights(level);
     let item_choice = WeightedChoice::new(&mut item_chances);
 
     let map_regions = 7;
@@ -192,40 +202,7 @@ pub fn no_room_items(
             if x >= MAP_WIDTH { break; }
 
             if !Object::is_blocked(x, y, map, characters) {
-                let item = match item_choice.ind_sample(&mut rand::thread_rng()) {
-                    Item::Heal => {
-                        // Create a health potion.
-                        Object::health_pot(x, y)
-                    },
-                    Item::LightningBoltScroll => {
-                        // Creates a lightning bolt scroll.
-                        Object::lightning_bolt_scroll(x, y)
-                    },
-                    Item::FireballScroll => {
-                        // Creates a fireball scroll
-                        Object::fireball_scroll(x, y)
-                    },
-                    Item::ConfusionScroll => {
-                        // Creates a confusion scroll
-                        Object::confusion_scroll(x, y)
-                    },
-                    Item::FearScroll => {
-                        // Creates a confusion scroll
-                        Object::confusion_scroll(x, y)
-                    },
-                    Item::HpUp => {
-                        // Creates a Health upgrade
-                        Object::health_up(x, y)
-                    },
-                    Item::PowUp => {
-                        // Creates a Power upgrade
-                        Object::power_up(x, y)
-                    },
-                    Item::DefUp => {
-                        // Creates a Defense upgrade
-                        Object::defense_up(x, y)
-                    },
-                };
+                let item = table.items[item_choice.ind_sample(&mut rand::thread_rng())].create(x, y, level, identity);
                 items.insert(*item_counter, item);
                 *item_counter += 1;
                 region_items += 1;
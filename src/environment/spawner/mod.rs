@@ -1,97 +1,129 @@
 pub mod character_spawns;
-use character_spawns::{ room_characters, no_room_characters };
+use character_spawns::{ room_characters, no_room_characters, den_characters, stairs_guardian };
 
 pub mod item_spawns;
-use item_spawns::{ room_items, no_room_items };
+use item_spawns::{ room_items, no_room_items, treasure_items, library_items };
+
+pub mod trap_spawns;
+use trap_spawns::{ room_traps, no_room_traps };
 
 use crate::environment::{ Map, MAP_WIDTH, MAP_HEIGHT, MapTheme };
-use crate::environment::map::Rect;
+use crate::environment::map::rooms::{ Room, RoomRole };
 use crate::objects::{ Object, Character };
+use crate::objects::npc::enemies::boss::generate_boss;
+use crate::objects::items::table::ItemTable;
+use crate::objects::items::identify::Identification;
 
 use std::collections::HashMap;
-use rand::*;
+
+use serde::{ Serialize, Deserialize };
 
 use tcod::colors::*;
 
 pub fn rooms_spawner(
-    rooms: &Vec<Rect>,
+    rooms: &[Room],
     items: &mut HashMap<i32, Object>,
     map: &Map,
     characters: &mut Vec<Character>,
+    start: (i32, i32),
+    table: &ItemTable,
+    identity: &Identification,
     level: u32,
     theme: MapTheme,
 ) {
-    // Ensures that there are no existing entities in the character, or item collections.
-    characters.clear();
-    items.clear();
-
     // Keeps track of total items spawned on a map.
-    let mut item_counter = 1;
-
-    for room in rooms {
-        room_characters(*room, &map, characters, level, theme);
-        room_items(*room, items, &map, characters, &mut item_counter, level);
+    // The stairs may already have been placed, so counting starts after them.
+    let mut item_counter = items.len() as i32 + 1;
+
+    // Each room is filled depending on the role it was given.
+    for room in rooms.iter() {
+        let rect = room.rect;
+        match room.role {
+            RoomRole::Ordinary => {
+                room_characters(rect, &map, characters, table, identity, level, theme);
+                room_items(rect, items, &map, characters, &mut item_counter, table, identity, level);
+                // The room the player starts in is kept free of traps.
+                if !rect.contains(start.0, start.1) {
+                    room_traps(rect, items, &map, characters, &mut item_counter, level, theme);
+                }
+            },
+            RoomRole::Treasure => {
+                treasure_items(rect, items, &map, characters, &mut item_counter, table, identity, level);
+                room_traps(rect, items, &map, characters, &mut item_counter, level, theme);
+            },
+            RoomRole::Den => {
+                den_characters(rect, &map, characters, table, identity, level, theme);
+            },
+            RoomRole::Shrine => {
+                let (x, y) = rect.center();
+                create_altar(items, x, y);
+                item_counter += 1;
+            },
+            RoomRole::Library => {
+                room_characters(rect, &map, characters, table, identity, level, theme);
+                library_items(rect, items, &map, characters, &mut item_counter, table, identity, level);
+            },
+            RoomRole::Stairs => {
+                stairs_guardian(rect, &map, characters, table, identity, level, theme);
+                room_items(rect, items, &map, characters, &mut item_counter, table, identity, level);
+            },
+            // Arenas are only made by the boss spawner.
+            RoomRole::Arena => {},
+        }
     }
-
-    // Create stairs at the center of the last room.
-    let (last_room_center_x, last_room_center_y) = rooms[rooms.len() - 1].center();
-    create_stairs(items, last_room_center_x, last_room_center_y);
 }
 
 pub fn no_rooms_spawner(
+    rooms: &[Room],
     items: &mut HashMap<i32, Object>,
     map: &Map,
     characters: &mut Vec<Character>,
+    table: &ItemTable,
+    identity: &Identification,
     level: u32,
     theme: MapTheme,
 ) {
-    // Ensures that there are no existing entities in the character, or item collections.
-    characters.clear();
-    items.clear();
-
     // Keeps track of total items spawned on a map.
-    let mut item_counter = 1;
-
-    no_room_characters(&map, characters, level, theme);
-    no_room_items(items, &map, characters, &mut item_counter, level);
+    // The stairs may already have been placed, so counting starts after them.
+    let mut item_counter = items.len() as i32 + 1;
 
-    let mut stairs_placed = true;
-    while stairs_placed {
-        let x = rand::thread_rng().gen_range(1, MAP_WIDTH - 1);
-        let y = rand::thread_rng().gen_range(1, MAP_HEIGHT - 1);
+    no_room_characters(&map, characters, table, identity, level, theme);
+    no_room_items(items, &map, characters, &mut item_counter, table, identity, level);
+    no_room_traps(items, &map, characters, &mut item_counter, level, theme);
 
-        if map[x as usize][y as usize].empty == true {
-            create_stairs(items, x, y);
-            stairs_placed = false;
-        }
+    // Any vaults which were stamped onto the map are filled with treasure.
+    for room in rooms.iter().filter(|room| room.role == RoomRole::Treasure) {
+        treasure_items(room.rect, items, &map, characters, &mut item_counter, table, identity, level);
     }
 }
 
-pub fn maze_spawner(
+pub fn boss_spawner(
+    rooms: &[Room],
     items: &mut HashMap<i32, Object>,
     map: &Map,
     characters: &mut Vec<Character>,
+    table: &ItemTable,
+    identity: &Identification,
     level: u32,
     theme: MapTheme,
 ) {
-    // Ensures that there are no existing entities in the character, or item collections.
-    characters.clear();
-    items.clear();
-
     // Keeps track of total items spawned on a map.
-    let mut item_counter = 1;
+    // The stairs may already have been placed, so counting starts after them.
+    let mut item_counter = items.len() as i32 + 1;
 
-    no_room_characters(&map, characters, level, theme);
-    no_room_items(items, &map, characters, &mut item_counter, level);
+    // The boss waits in the middle of the arena.
+    if let Some(arena) = rooms.iter().find(|room| room.role == RoomRole::Arena) {
+        let (arena_x, arena_y) = arena.rect.center();
+        characters.push(generate_boss(arena_x, arena_y, level, theme, table, identity));
+    }
 
-    match rand::thread_rng().gen_range(0, 4) {
-        0 => create_stairs(items, 1, 1),
-        1 => create_stairs(items, 1, 41),
-        2 => create_stairs(items, 79, 1),
-        _ => create_stairs(items, 79, 41),
+    // The antechamber holds some supplies for the fight ahead.
+    for room in rooms.iter().filter(|room| room.role == RoomRole::Ordinary) {
+        room_items(room.rect, items, &map, characters, &mut item_counter, table, identity, level);
     }
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Transition {
     pub level: u32,
     pub value: u32,
@@ -116,6 +148,8 @@ pub fn create_stairs(items: &mut HashMap<i32, Object>, x: i32, y: i32) {
         fighter: None,
         ai: None,
         item: None,
+        trap: None,
+        equipment: None,
         level: 1,
         always_visible: true,
     };
@@ -130,3 +164,81 @@ pub fn create_stairs(items: &mut HashMap<i32, Object>, x: i32, y: i32) {
     }
     items.insert(stairs_id, stairs); // Finally, inserts stairs into the items hashmap.
 }
+
+pub fn create_altar(items: &mut HashMap<i32, Object>, x: i32, y: i32) {
+    let altar = Object {
+        x: x,
+        y: y,
+        char: '_',
+        color: LIGHTEST_YELLOW,
+        name: "Altar".into(),
+        blocks: false,
+        alive: false,
+        corpse_type: "Altar".into(),
+        fighter: None,
+        ai: None,
+        item: None,
+        trap: None,
+        equipment: None,
+        level: 1,
+        always_visible: true,
+    };
+
+    let mut altar_id = 1; // Sets up id for the altar to use in items hashmap.
+    for _ in 0..items.len() {
+        if items.contains_key(&altar_id) {
+            altar_id += 1;
+        } else {
+            break;
+        }
+    }
+    items.insert(altar_id, altar); // Finally, inserts the altar into the items hashmap.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::map::{ Rect, create_room, room_sorter };
+    use crate::environment::map::rooms::assign_room_roles;
+    use crate::environment::map::tiles::Tile;
+
+    use rand::Rng;
+
+    const COLORS: [Color; 7] = [GREY, DARK_SEPIA, SEPIA, LIGHT_SEPIA, DARKER_SEPIA, DARK_GREY, DARKEST_GREY];
+
+    // A scattering of rooms, in the random order the rectangles generator would make them.
+    fn shuffled_rects() -> Vec<Rect> {
+        let mut rects: Vec<Rect> = (0..6).map(|i| Rect::new(2 + i * 12, 4 + (i % 3) * 12, 8, 8)).collect();
+        rand::thread_rng().shuffle(&mut rects);
+        rects
+    }
+
+    #[test]
+    fn start_room_is_ordinary_and_free_of_traps() {
+        let table = ItemTable::built_in();
+        let identity = Identification::new(&table);
+
+        for _ in 0..50 {
+            let mut rects = shuffled_rects();
+            let mut map = vec![vec![Tile::wall(&COLORS); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+            for rect in rects.iter() {
+                create_room(*rect, &mut map, &COLORS);
+            }
+
+            // The player starts in the first room made, which is rarely the first once the rooms are sorted.
+            let start = rects[0].center();
+            room_sorter(&mut rects);
+            let rooms = assign_room_roles(&rects, start);
+
+            let start_room = rooms.iter().find(|room| room.rect.contains(start.0, start.1)).unwrap();
+            assert_eq!(start_room.role, RoomRole::Ordinary);
+            assert_eq!(rooms.iter().filter(|room| room.role == RoomRole::Stairs).count(), 1);
+
+            let mut items = HashMap::new();
+            let mut characters = vec![];
+            rooms_spawner(&rooms, &mut items, &map, &mut characters, start, &table, &identity, 10, MapTheme::Fire);
+            let trapped = items.values().any(|item| item.trap.is_some() && start_room.rect.contains(item.x, item.y));
+            assert!(!trapped);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::environment::{ Map, MapTheme, MAP_WIDTH, MAP_HEIGHT };
+use crate::environment::map::Rect;
+use crate::objects::{ Object, Character };
+use crate::objects::traps::TrapKind;
+use super::*;
+
+use std::collections::HashMap;
+
+use rand::*;
+use rand::distributions::{ IndependentSample, Weighted, WeightedChoice };
+
+fn trap_weighting(level: u32) -> [Weighted<TrapKind>; 5] {
+    [
+        Weighted {
+            weight: 30,
+            item: TrapKind::Pit,
+        },
+        Weighted {
+            weight: from_dungeon_level(
+                &[
+                    Transition { level: 2, value: 20, },
+                ],
+                level,
+            ),
+            item: TrapKind::PoisonDart,
+        },
+        Weighted {
+            weight: from_dungeon_level(
+                &[
+                    Transition { level: 3, value: 10, },
+                    Transition { level: 8, value: 20, },
+                ],
+                level,
+            ),
+            item: TrapKind::Alarm,
+        },
+        Weighted {
+            weight: from_dungeon_level(
+                &[
+                    Transition { level: 4, value: 10, },
+                ],
+                level,
+            ),
+            item: TrapKind::Teleport,
+        },
+        Weighted {
+            weight: from_dungeon_level(
+                &[
+                    Transition { level: 5, value: 15, },
+                    Transition { level: 10, value: 25, },
+                ],
+                level,
+            ),
+            item: TrapKind::ElementalBurst,
+        },
+    ]
+}
+
+pub fn room_traps(
+    room: Rect,
+    items: &mut HashMap<i32, Object>,
+    map: &Map,
+    characters: &mut Vec<Character>,
+    item_counter: &mut i32,
+    level: u32,
+    theme: MapTheme,
+) {
+    // Decides maximum number of traps per room.
+    let max_traps = from_dungeon_level(
+        &[
+            Transition { level: 1, value: 1 },
+            Transition { level: 6, value: 2 },
+        ],
+        level,
+    );
+
+    let mut trap_chances = trap_weighting(level);
+    let trap_choice = WeightedChoice::new(&mut trap_chances);
+
+    // Choose random number of traps.
+    let num_traps = rand::thread_rng().gen_range(0, max_traps + 1);
+
+    for _ in 0..num_traps {
+        // Select random spot for the trap.
+        let x = rand::thread_rng().gen_range(room.x1 + 1, room.x2);
+        let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
+
+        // Traps aren't placed beneath other items, or the stairs.
+        let occupied = items.values().any(|item| item.pos() == (x, y));
+        if !Object::is_blocked(x, y, map, characters) && !occupied {
+            let kind = trap_choice.ind_sample(&mut rand::thread_rng());
+            items.insert(*item_counter, Object::new_trap(x, y, kind, level, theme));
+            *item_counter += 1;
+        }
+    }
+}
+
+pub fn no_room_traps(
+    items: &mut HashMap<i32, Object>,
+    map: &Map,
+    characters: &mut Vec<Character>,
+    item_counter: &mut i32,
+    level: u32,
+    theme: MapTheme,
+) {
+    // Decides how many traps are scattered across the map.
+    let num_traps = from_dungeon_level(
+        &[
+            Transition { level: 1, value: 3 },
+            Transition { level: 6, value: 6 },
+            Transition { level: 12, value: 9 },
+        ],
+        level,
+    );
+
+    let mut trap_chances = trap_weighting(level);
+    let trap_choice = WeightedChoice::new(&mut trap_chances);
+
+    let mut traps_placed = 0;
+    let mut attempts = 0;
+    let max_tries = 100;
+
+    while traps_placed < num_traps && attempts < max_tries {
+        // Select random spot for the trap.
+        let x = rand::thread_rng().gen_range(1, MAP_WIDTH - 1);
+        let y = rand::thread_rng().gen_range(1, MAP_HEIGHT - 1);
+
+        let occupied = items.values().any(|item| item.pos() == (x, y));
+        if map[x as usize][y as usize].empty && !Object::is_blocked(x, y, map, characters) && !occupied {
+            let kind = trap_choice.ind_sample(&mut rand::thread_rng());
+            items.insert(*item_counter, Object::new_trap(x, y, kind, level, theme));
+            *item_counter += 1;
+            traps_placed += 1;
+        } else {
+            attempts += 1;
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::*;
+use crate::objects::items::Category;
 
 const INVENTORY_WIDTH: i32 = 50;
+const POWERS_WIDTH: i32 = 60;
 
 pub fn menu<T: AsRef<str>>(
     header: &str,
@@ -73,7 +75,7 @@ pub fn menu<T: AsRef<str>>(
     }
 }
 
-pub fn inventory_menu(player: &Character, header: &str, root: &mut Root) -> Option<usize> {
+pub fn inventory_menu(game: &Game, player: &Character, header: &str, root: &mut Root) -> Option<usize> {
     // Collects inventory items, using an empty vec, in case the inventory is inaccessible for some reason.
     let empty_vec = Vec::new();
     let inventory = match &player.inventory {
@@ -85,7 +87,8 @@ pub fn inventory_menu(player: &Character, header: &str, root: &mut Root) -> Opti
     let options = if inventory.len() == 0 {
             vec!["Inventory is empty.".into()]
         } else {
-            inventory.iter().map(|item| item.name.clone()).collect()
+            // Items are listed by the names the player knows them by.
+            inventory.iter().map(|item| game.identity.name_of(item)).collect()
     };
 
     // Creates a menu, and collects the choice made by the player.
@@ -99,6 +102,50 @@ pub fn inventory_menu(player: &Character, header: &str, root: &mut Root) -> Opti
     }
 }
 
+// Shows the player's powers, and returns the one which was chosen.
+pub fn powers_menu(game: &Game, header: &str, root: &mut Root) -> Option<usize> {
+    let options = if game.powers.is_empty() {
+        vec!["You haven't absorbed any powers yet.".into()]
+    } else {
+        game.powers.iter().map(|power| power.description()).collect()
+    };
+
+    let power_index = menu(header, &options, POWERS_WIDTH, root);
+    if game.powers.is_empty() {
+        None
+    } else {
+        power_index
+    }
+}
+
+// Shows the equipment the player is wearing, and returns the piece which was chosen.
+pub fn equipment_menu(game: &Game, header: &str, root: &mut Root) -> Option<usize> {
+    let options = if game.equipment.is_empty() {
+        vec!["You aren't wearing any equipment.".into()]
+    } else {
+        game.equipment
+            .iter()
+            .map(|item| match item.equipment {
+                Some(equipment) => format!("{}: {} ({})", equipment.slot.name(), item.name, equipment.description()),
+                None => item.name.clone(),
+            })
+            .collect()
+    };
+
+    let equipment_index = menu(header, &options, INVENTORY_WIDTH, root);
+    if game.equipment.is_empty() {
+        None
+    } else {
+        equipment_index
+    }
+}
+
+// Lists every kind of item an unidentified item could be, and returns the one the player guessed.
+pub fn guess_menu(game: &Game, category: Category, header: &str, root: &mut Root) -> Option<String> {
+    let candidates = game.identity.candidates(category);
+    menu(header, &candidates, INVENTORY_WIDTH, root).map(|index| candidates[index].clone())
+}
+
 // Uses the menu function to display a simple message box.
 // Message displayed is the "text" variable msgbox takes.
 pub fn msgbox(text: &str, width: i32, root: &mut Root) {
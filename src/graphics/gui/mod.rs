@@ -2,6 +2,7 @@ pub mod menu;
 
 use crate::*;
 use crate::objects::*;
+use crate::objects::effects::EffectKind;
 
 use serde::{ Serialize, Deserialize };
 
@@ -58,14 +59,28 @@ fn render_panel(tcod: &mut Tcod, game: &Game, characters: &[Character], items: &
         format!("Dungeon level: {}", game.dungeon_level),
     );
 
+    // Show the status effects on the player, one per line, beneath the mouse-over names.
+    let effects = player.fighter.map_or(vec![], |f| f.effects.active());
+    for (line, effect) in effects.iter().take(PANEL_HEIGHT as usize - 3).enumerate() {
+        tcod.panel.set_default_foreground(effect.kind.color());
+        tcod.panel.print_ex(
+            1,
+            3 + line as i32,
+            BackgroundFlag::None,
+            TextAlignment::Left,
+            format!("{} ({})", effect.kind.name(), effect.turns),
+        );
+    }
+
     // Show the list of objects beneath the mouse.
+    // Names, and the abilities listed alongside them, are wrapped so they don't run into the messages.
     tcod.panel.set_default_foreground(LIGHT_GREY);
-    tcod.panel.print_ex(
+    tcod.panel.print_rect(
         1,
         2,
-        BackgroundFlag::None,
-        TextAlignment::Left,
-        get_names_under_mouse(tcod.mouse, player, characters, items, &tcod.fov),
+        BAR_WIDTH,
+        PANEL_HEIGHT - 2,
+        get_names_under_mouse(tcod.mouse, game, player, characters, items, &tcod.fov),
     );
 
     // Blit the contents of 'panel' to the root console.
@@ -105,6 +120,7 @@ impl Messages {
 
 fn get_names_under_mouse(
     mouse: Mouse,
+    game: &Game,
     player: &Object,
     characters: &[Character],
     items: &HashMap<i32, Object>,
@@ -118,7 +134,14 @@ fn get_names_under_mouse(
     let character_names = characters
         .iter()
         .filter(|cha| cha.object.pos() == (x, y) && fov_map.is_in_fov(cha.object.x, cha.object.y))
-        .map(|cha| cha.object.name.clone())
+        .filter(|cha| !cha.object.has_effect(EffectKind::Invisibility) || cha.object.distance_to(player) < 2.0)
+        .map(|cha| {
+            // Monsters with a trait ability have it listed alongside their name.
+            match cha.object.fighter.and_then(|f| f.ability) {
+                Some(ability) => format!("{} ({})", cha.object.name, ability.description()),
+                None => cha.object.name.clone(),
+            }
+        })
         .collect::<Vec<_>>();
 
     // If player is at those coordinates, it is added to the list first.
@@ -128,8 +151,9 @@ fn get_names_under_mouse(
 
     // Adds items to vector first so they always appear at the top of the list.
     for (_, item) in items {
-        if item.pos() == (x, y) && fov_map.is_in_fov(item.x, item.y) {
-            names.push(item.name.clone());
+        let hidden = item.trap.map_or(false, |t| t.hidden);
+        if item.pos() == (x, y) && fov_map.is_in_fov(item.x, item.y) && !hidden {
+            names.push(game.identity.name_of(item));
         }
     }
 
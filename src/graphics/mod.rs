@@ -2,6 +2,7 @@ pub mod gui;
 
 use crate::*;
 use crate::objects::*;
+use crate::objects::effects::EffectKind;
 use crate::environment::*;
 use gui::render_gui;
 
@@ -17,7 +18,7 @@ pub fn render_all(
 ) {
     if fov_recompute {
         //Recomputes FOV is needed, such as player movement
-        tcod.fov.compute_fov(player.x, player.y, TORCH_RADIUS, FOV_LIGHT_WALLS, FOV_ALGO);
+        tcod.fov.compute_fov(player.x, player.y, player.sight_radius(), FOV_LIGHT_WALLS, FOV_ALGO);
     }
 
     // Scans the map
@@ -37,6 +38,7 @@ pub fn render_all(
             };
 
             // If tile is currently in FOV, set the tiles "explored" variable to true.
+            game.map[x as usize][y as usize].visible = visible;
             let explored = &mut game.map[x as usize][y as usize].explored;
             if visible {
                 //Since it's visible, explore it
@@ -60,7 +62,7 @@ fn draw_objects(tcod: &mut Tcod, game: &mut Game, items: &HashMap<i32, Object>,
     // Draws items, then draws characters.
     // Characters are second, so that they have visibility priority over items.
     draw_items(tcod, game, items);
-    draw_chars(tcod, game, characters);
+    draw_chars(tcod, game, characters, player);
 
     // Finally, it renders the player.
     player.draw(&mut tcod.con);
@@ -79,7 +81,8 @@ fn draw_objects(tcod: &mut Tcod, game: &mut Game, items: &HashMap<i32, Object>,
 
 fn draw_items(tcod: &mut Tcod, game: &mut Game, items: &HashMap<i32, Object>) {
     // Searches through items hashmap.
-    for item in items.values() {
+    // Hidden traps are skipped, as the player shouldn't know they're there.
+    for item in items.values().filter(|item| item.trap.map_or(true, |t| !t.hidden)) {
         // If item is in FOV, or "always_visible" variable is true in the location of an explored tile.
         // Draw the item.
         if tcod.fov.is_in_fov(item.x, item.y) ||
@@ -89,11 +92,13 @@ fn draw_items(tcod: &mut Tcod, game: &mut Game, items: &HashMap<i32, Object>) {
     }
 }
 
-fn draw_chars(tcod: &mut Tcod, game: &mut Game, characters: &[Character]) {
+fn draw_chars(tcod: &mut Tcod, game: &mut Game, characters: &[Character], player: &Object) {
     // Sorts character list to place non-blocking (corpses) first.
     // This allows living characters to appear on top of them.
+    // Invisible characters only show up once the player is right next to them.
     let mut to_draw: Vec<_> = characters
         .iter()
+        .filter(|c| !c.object.has_effect(EffectKind::Invisibility) || c.object.distance_to(player) < 2.0)
         .filter(|c| {
             tcod.fov.is_in_fov(c.object.x, c.object.y) ||
             (c.object.always_visible && game.map[c.object.x as usize][c.object.y as usize].explored)
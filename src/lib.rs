@@ -5,8 +5,12 @@ pub mod graphics;
 pub mod pathing;
 
 use objects::{ Object, Character };
+use objects::effects::EffectKind;
 use environment::*;
+use environment::schedule::LevelSchedule;
+use objects::items::table::ItemTable;
 use controls::{ handle_keys, PlayerAction };
+use controls::travel::auto_move_step;
 use graphics::render_all;
 use graphics::gui::menu::{ menu, msgbox };
 
@@ -18,7 +22,7 @@ use std::io::{ Read, Write };
 use tcod::console::*;
 use tcod::colors::*;
 use tcod::map::Map as FovMap;
-use tcod::input::{ self, Event, Key, Mouse };
+use tcod::input::{ self, Event, Key, KeyCode, Mouse };
 
 const LIMIT_FPS: i32 = 60; // 20 frames-per-second maximum
 
@@ -45,6 +49,22 @@ const LEVEL_SCREEN_WIDTH: i32 = 40;
 
 const CHARACTER_SCREEN_WIDTH: i32 = 30;
 
+// Chances of finding a hidden tile next to the player.
+// Searching is deliberate, and far more reliable than simply walking past.
+const SEARCH_CHANCE: f32 = 0.75;
+const PERCEPTION_CHANCE: f32 = 0.1;
+
+// Chance of a monster carrying each item, up to as many as it can hold.
+const CARRY_CHANCE: f32 = 0.2;
+
+// Base chance of successfully disarming a trap.
+const DISARM_CHANCE: f32 = 0.6;
+
+// How far away sleeping monsters can hear different noises from.
+const COMBAT_NOISE: i32 = 6;
+const TRAP_NOISE: i32 = 8;
+const ALARM_NOISE: i32 = 30;
+
 pub type Point = (u32, u32); // (x, y)
 
 // Struct to contain the game.
@@ -204,7 +224,11 @@ fn load_game() -> Result<(Game, Vec<Character>, HashMap<i32, Object>, Character)
     file.read_to_string(&mut json_save_state)?;
 
     // Loads the data imported to string to the different game elements.
-    let result = serde_json::from_str::<(Game, Vec<Character>, HashMap<i32, Object>, Character)>(&json_save_state)?;
+    let mut result = serde_json::from_str::<(Game, Vec<Character>, HashMap<i32, Object>, Character)>(&json_save_state)?;
+
+    // The level schedule and item table aren't saved, so they are read again from their files.
+    result.0.schedule = LevelSchedule::load(&mut result.0.messages);
+    result.0.item_table = ItemTable::load(&mut result.0.messages);
 
     // Returns those elements in an Ok() to be unpacked into the game.
     Ok(result)
@@ -219,6 +243,7 @@ fn play_game(
 ) {
     // Force FOV "recompute" first time through the game loop
     let mut previous_player_position = (-1, -1);
+    let mut previous_blinded = false;
 
     // Keeps the core game loop happening so long as the window remains open.
     while !tcod.root.window_closed() {
@@ -232,7 +257,10 @@ fn play_game(
         }
 
         // Renders the screen
-        let fov_recompute = previous_player_position != (player.object.pos());
+        // Being blinded, or getting sight back, changes how far the player can see.
+        let blinded = player.object.has_effect(EffectKind::Blindness);
+        let fov_recompute = previous_player_position != (player.object.pos()) || blinded != previous_blinded;
+        previous_blinded = blinded;
         render_all(&mut tcod, &mut game, &characters[..], &items, fov_recompute, &mut player.object);
 
         // Moves everything to render onto the main console.
@@ -243,19 +271,85 @@ fn play_game(
 
         // Handles keys, and exits game if prompted
         previous_player_position = player.object.pos();
-        let player_action = handle_keys(&mut tcod, &mut game, &mut characters, &mut items, &mut player);
+        let player_action = if game.auto_move.is_some() {
+            // While moving automatically, any key press stops the player.
+            if tcod.key.code != KeyCode::NoKey {
+                game.auto_move = None;
+                game.messages.add("You stop.", LIGHT_GREY);
+                PlayerAction::DidntTakeTurn
+            } else {
+                auto_move_step(&tcod, &mut game, &mut characters, &items, &mut player)
+            }
+        } else {
+            handle_keys(&mut tcod, &mut game, &mut characters, &mut items, &mut player)
+        };
         if player_action == PlayerAction::Exit {
             save_game(game, characters, items, player).unwrap();
             break;
         }
 
+        // Lets the player know when they walk into a room with a special purpose.
+        game.announce_room(previous_player_position, player.object.pos());
+
+        // Springs any trap the player has just stepped on to.
+        let (player_x, player_y) = player.object.pos();
+        if player_action == PlayerAction::TookTurn && (player_x, player_y) != previous_player_position {
+            if let Some(trap_id) = Object::trap_at(player_x, player_y, items) {
+                Object::spring_trap(trap_id, None, &tcod.fov, game, characters, items, &mut player.object);
+            }
+        }
+
+        // Gives the player a passive chance to notice any nearby secrets.
+        if player.object.alive && player_action != PlayerAction::DidntTakeTurn {
+            Object::search_for_secrets(tcod, game, &player.object, PERCEPTION_CHANCE);
+            Object::reveal_traps(player_x, player_y, 1.5, PERCEPTION_CHANCE, game, items);
+        }
+
         // Lets monsters take their turn
         if player.object.alive && player_action != PlayerAction::DidntTakeTurn {
-            for id in 0..characters.len() {
-                if characters[id].object.ai.is_some() {
-                    Object::ai_take_turn(id, &tcod, &mut game, &mut characters, &mut player.object);
+            // A slowed player gives monsters an extra round, and a hasted player can skip them entirely.
+            let rounds = 2 - player.object.actions_per_turn();
+
+            for _ in 0..rounds {
+                for id in 0..characters.len() {
+                    // Monsters can be hasted, or slowed, too.
+                    for _ in 0..characters[id].object.actions_per_turn() {
+                        if characters[id].object.ai.is_none() {
+                            break;
+                        }
+                        let previous_position = characters[id].object.pos();
+                        Object::ai_take_turn(id, &tcod, &mut game, &mut characters, &mut player.object);
+
+                        // Monsters can set off traps, and pick up items, too.
+                        let (monster_x, monster_y) = characters[id].object.pos();
+                        if (monster_x, monster_y) != previous_position {
+                            if let Some(trap_id) = Object::trap_at(monster_x, monster_y, items) {
+                                Object::spring_trap(trap_id, Some(id), &tcod.fov, game, characters, items, &mut player.object);
+                            }
+                            if characters[id].object.alive {
+                                Object::monster_pick_up(id, tcod, game, characters, items);
+                            }
+                        }
+                    }
                 }
             }
+
+            // Noises only last for a single turn.
+            game.noises.clear();
+
+            // Status effects tick, and wear off, once per turn.
+            if player.object.alive {
+                Object::tick_all_effects(&mut game, &mut characters, &mut player.object);
+                Object::powers_turn(&game, &mut player.object);
+            }
+        }
+
+        // Anything slain this turn splits, explodes, or leaves something behind.
+        Object::resolve_deaths(&mut game, &mut characters, &mut items, &mut player.object);
+
+        // The player absorbs the essence of anything slain this turn.
+        if !game.essences.is_empty() {
+            Object::absorb_essences(&mut game, &mut player.object);
         }
     }
 }
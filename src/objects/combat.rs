@@ -0,0 +1,214 @@
+use super::Object;
+use super::npc::{ DamageType, RangedAttack };
+
+use rand::Rng;
+
+use serde::{ Serialize, Deserialize };
+
+use tcod::colors::*;
+
+// Every attack has at least this much of a chance to hit, and to miss, no matter how evasive the target is.
+const MIN_HIT_CHANCE: i32 = 5;
+const MAX_HIT_CHANCE: i32 = 95;
+
+// Damage is spread this far either side of the attack's power.
+const DAMAGE_VARIANCE: f32 = 0.2;
+
+// Chance of a critical hit, and how much harder it hits.
+const CRITICAL_CHANCE: i32 = 5;
+const CRITICAL_MULTIPLIER: f32 = 1.5;
+
+// Extra damage for each level the attacker has over the defender.
+const LEVEL_BONUS: f32 = 0.1;
+
+// Everything the resolver needs to know about an attack.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attack {
+    pub power: i32,
+    pub accuracy: Option<i32>, // Attacks without an accuracy never miss.
+    pub level: i32,
+    pub kind: DamageType,
+    pub can_critical: bool,
+    pub ignores_defense: bool,
+}
+
+impl Attack {
+    // A blow from a character, using its own power and accuracy.
+    pub fn melee(attacker: &Object, kind: DamageType) -> Attack {
+        Attack {
+            power: attacker.fighter.map_or(0, |f| f.power),
+            accuracy: Some(attacker.fighter.map_or(0, |f| f.accuracy)),
+            level: attacker.level,
+            kind: kind,
+            can_critical: true,
+            ignores_defense: false,
+        }
+    }
+
+    // A shot from a character's ranged attack.
+    pub fn ranged(attacker: &Object, ranged: &RangedAttack) -> Attack {
+        Attack {
+            power: ranged.power,
+            accuracy: Some(attacker.fighter.map_or(0, |f| f.accuracy)),
+            level: attacker.level,
+            kind: ranged.projectile.damage_type(),
+            can_critical: true,
+            ignores_defense: false,
+        }
+    }
+
+    // Spells, and traps, hit for a set amount of damage, which armour does nothing against.
+    pub fn flat(damage: i32, kind: DamageType) -> Attack {
+        Attack {
+            power: damage,
+            accuracy: None,
+            level: 0,
+            kind: kind,
+            can_critical: false,
+            ignores_defense: true,
+        }
+    }
+
+    // Gives an attack a chance to miss.
+    pub fn with_accuracy(mut self, accuracy: i32) -> Attack {
+        self.accuracy = Some(accuracy);
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Miss,
+    Blocked, // Hit, but didn't get through the target's defense.
+    Hit,
+    Critical,
+}
+
+// The result of an attack, for both the combat log and the statistics to use.
+// Damage is after the defender's resistances, so it is exactly what the defender loses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AttackResult {
+    pub outcome: Outcome,
+    pub damage: i32,
+    pub kind: DamageType,
+    pub hit_chance: i32, // 100 for attacks which can't miss.
+}
+
+impl AttackResult {
+    pub fn landed(&self) -> bool {
+        self.damage > 0
+    }
+
+    // Describes the attack for the combat log, such as "Player attacks blob (85%), dealing 4 damage."
+    pub fn describe(&self, attacker: &str, verb: &str, defender: &str) -> String {
+        match self.outcome {
+            Outcome::Miss => format!("{} {} {} ({}%), but misses.", attacker, verb, defender, self.hit_chance),
+            Outcome::Blocked => format!("{} {} {} ({}%), but it has no effect!", attacker, verb, defender, self.hit_chance),
+            Outcome::Hit => format!(
+                "{} {} {} ({}%), dealing {} damage.",
+                attacker, verb, defender, self.hit_chance, self.damage
+            ),
+            Outcome::Critical => format!(
+                "{} {} {} ({}%), landing a critical hit for {} damage!",
+                attacker, verb, defender, self.hit_chance, self.damage
+            ),
+        }
+    }
+
+    // Color of the combat log message. Hits use the attacker's own color.
+    pub fn color(&self, attacker_color: Color) -> Color {
+        match self.outcome {
+            Outcome::Miss | Outcome::Blocked => WHITE,
+            Outcome::Hit => attacker_color,
+            Outcome::Critical => GOLD,
+        }
+    }
+}
+
+// Works out how an attack against a defender plays out.
+// The attack rolls to hit against the defender's evasion, then its damage is varied, possibly critical, and reduced by defense.
+// Finally, the defender's resistances and weaknesses are applied.
+pub fn resolve(attack: &Attack, defender: &Object) -> AttackResult {
+    let mut rng = rand::thread_rng();
+    let (defense, evasion) = defender.fighter.map_or((0, 0), |f| (f.defense, f.evasion));
+
+    let hit_chance = match attack.accuracy {
+        Some(accuracy) => (accuracy - evasion).max(MIN_HIT_CHANCE).min(MAX_HIT_CHANCE),
+        None => 100,
+    };
+    if rng.gen_range(0, 100) >= hit_chance {
+        return AttackResult {
+            outcome: Outcome::Miss,
+            damage: 0,
+            kind: attack.kind,
+            hit_chance: hit_chance,
+        };
+    }
+
+    let mut damage = attack.power as f32 * rng.gen_range(1.0 - DAMAGE_VARIANCE, 1.0 + DAMAGE_VARIANCE);
+    if attack.level > defender.level {
+        damage *= 1.0 + LEVEL_BONUS * (attack.level - defender.level) as f32;
+    }
+
+    let critical = attack.can_critical && rng.gen_range(0, 100) < CRITICAL_CHANCE;
+    if critical {
+        damage *= CRITICAL_MULTIPLIER;
+    }
+    if !attack.ignores_defense {
+        damage -= defense as f32;
+    }
+
+    let damage = defender.resisted_damage((damage.round() as i32).max(0), attack.kind);
+    let outcome = if damage == 0 {
+        Outcome::Blocked
+    } else if critical {
+        Outcome::Critical
+    } else {
+        Outcome::Hit
+    };
+
+    AttackResult {
+        outcome: outcome,
+        damage: damage,
+        kind: attack.kind,
+        hit_chance: hit_chance,
+    }
+}
+
+// Running totals of every attack made by, and against, the player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CombatStats {
+    pub attacks: i32,
+    pub hits: i32,
+    pub misses: i32,
+    pub criticals: i32,
+    pub damage_dealt: i32,
+    pub attacks_received: i32,
+    pub dodged: i32,
+    pub damage_received: i32,
+}
+
+impl CombatStats {
+    // Records an attack the player made.
+    pub fn record_dealt(&mut self, result: &AttackResult) {
+        self.attacks += 1;
+        match result.outcome {
+            Outcome::Miss => self.misses += 1,
+            Outcome::Critical => {
+                self.hits += 1;
+                self.criticals += 1;
+            },
+            _ => self.hits += 1,
+        }
+        self.damage_dealt += result.damage;
+    }
+
+    // Records an attack made against the player.
+    pub fn record_received(&mut self, result: &AttackResult) {
+        self.attacks_received += 1;
+        if result.outcome == Outcome::Miss {
+            self.dodged += 1;
+        }
+        self.damage_received += result.damage;
+    }
+}
@@ -0,0 +1,348 @@
+use crate::environment::Game;
+
+use super::{ Object, Character };
+use super::npc::DamageType;
+
+use serde::{ Serialize, Deserialize };
+
+use tcod::colors::*;
+
+// Every kind of status effect, in the order they're kept in.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EffectKind {
+    Poison,
+    Burning,
+    Regeneration,
+    Haste,
+    Slow,
+    Confusion,
+    Fear,
+    Blindness,
+    Invisibility,
+}
+
+// How a new effect combines with one of the same kind which is already active.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stacking {
+    Refresh, // Keeps whichever duration, and potency, is higher.
+    Intensify, // Potencies add together, so doses build up.
+    Extend, // Durations add together.
+}
+
+impl EffectKind {
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            EffectKind::Poison => Stacking::Intensify,
+            EffectKind::Confusion | EffectKind::Fear | EffectKind::Invisibility => Stacking::Extend,
+            _ => Stacking::Refresh,
+        }
+    }
+
+    // Haste and slow cancel each other out, rather than both being active at once.
+    pub fn opposite(&self) -> Option<EffectKind> {
+        match self {
+            EffectKind::Haste => Some(EffectKind::Slow),
+            EffectKind::Slow => Some(EffectKind::Haste),
+            _ => None,
+        }
+    }
+
+    // Name of the effect, as shown in the GUI panel.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EffectKind::Poison => "Poisoned",
+            EffectKind::Burning => "Burning",
+            EffectKind::Regeneration => "Regenerating",
+            EffectKind::Haste => "Hasted",
+            EffectKind::Slow => "Slowed",
+            EffectKind::Confusion => "Confused",
+            EffectKind::Fear => "Afraid",
+            EffectKind::Blindness => "Blind",
+            EffectKind::Invisibility => "Invisible",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            EffectKind::Poison => LIME,
+            EffectKind::Burning => FLAME,
+            EffectKind::Regeneration => LIGHT_GREEN,
+            EffectKind::Haste => LIGHT_AZURE,
+            EffectKind::Slow => LIGHT_BLUE,
+            EffectKind::Confusion => LIGHTER_HAN,
+            EffectKind::Fear => DARKER_PURPLE,
+            EffectKind::Blindness => LIGHT_YELLOW,
+            EffectKind::Invisibility => LIGHT_GREY,
+        }
+    }
+
+    fn start_message(&self) -> &'static str {
+        match self {
+            EffectKind::Poison => "is poisoned!",
+            EffectKind::Burning => "is set alight!",
+            EffectKind::Regeneration => "begins to regenerate.",
+            EffectKind::Haste => "speeds up!",
+            EffectKind::Slow => "slows down!",
+            EffectKind::Confusion => "begins to stumble around in confusion!",
+            EffectKind::Fear => "is filled with terror!",
+            EffectKind::Blindness => "is blinded!",
+            EffectKind::Invisibility => "fades from sight!",
+        }
+    }
+
+    fn end_message(&self) -> &'static str {
+        match self {
+            EffectKind::Poison => "is no longer poisoned.",
+            EffectKind::Burning => "is no longer burning.",
+            EffectKind::Regeneration => "stops regenerating.",
+            EffectKind::Haste => "is no longer hasted.",
+            EffectKind::Slow => "is no longer slowed.",
+            EffectKind::Confusion => "is no longer confused.",
+            EffectKind::Fear => "is no longer scared.",
+            EffectKind::Blindness => "can see again.",
+            EffectKind::Invisibility => "fades back into view.",
+        }
+    }
+}
+
+// A single active effect.
+// Potency is the damage, or healing, done each turn, where the effect does any.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: EffectKind,
+    pub turns: i32,
+    pub potency: i32,
+}
+
+// All of the effects on a fighter, with a slot for each kind, in the same order as EffectKind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffects {
+    slots: [Option<StatusEffect>; 9],
+}
+
+impl StatusEffects {
+    pub fn get(&self, kind: EffectKind) -> Option<StatusEffect> {
+        self.slots[kind as usize]
+    }
+
+    pub fn has(&self, kind: EffectKind) -> bool {
+        self.slots[kind as usize].is_some()
+    }
+
+    pub fn remove(&mut self, kind: EffectKind) -> Option<StatusEffect> {
+        self.slots[kind as usize].take()
+    }
+
+    // Returns every active effect.
+    pub fn active(&self) -> Vec<StatusEffect> {
+        self.slots.iter().filter_map(|slot| *slot).collect()
+    }
+
+    // Adds an effect, combining it with any existing effect of the same kind.
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let slot = &mut self.slots[effect.kind as usize];
+        *slot = match *slot {
+            None => Some(effect),
+            Some(current) => Some(match effect.kind.stacking() {
+                Stacking::Refresh => StatusEffect {
+                    kind: effect.kind,
+                    turns: current.turns.max(effect.turns),
+                    potency: current.potency.max(effect.potency),
+                },
+                Stacking::Intensify => StatusEffect {
+                    kind: effect.kind,
+                    turns: current.turns.max(effect.turns),
+                    potency: current.potency + effect.potency,
+                },
+                Stacking::Extend => StatusEffect {
+                    kind: effect.kind,
+                    turns: current.turns + effect.turns,
+                    potency: current.potency.max(effect.potency),
+                },
+            }),
+        };
+    }
+
+    // Counts every effect down by a turn, and returns the ones which have worn off.
+    fn tick(&mut self) -> Vec<EffectKind> {
+        let mut expired = vec![];
+        for slot in self.slots.iter_mut() {
+            if let Some(effect) = slot.as_mut() {
+                effect.turns -= 1;
+                if effect.turns <= 0 {
+                    expired.push(effect.kind);
+                    *slot = None;
+                }
+            }
+        }
+        expired
+    }
+}
+
+impl Object {
+    pub fn has_effect(&self, kind: EffectKind) -> bool {
+        self.fighter.map_or(false, |f| f.effects.has(kind))
+    }
+
+    // Applies a status effect, and lets the player know about it if they can see it happen.
+    // An effect which meets its opposite cancels it out instead.
+    pub fn add_effect(&mut self, kind: EffectKind, turns: i32, potency: i32, game: &mut Game) {
+        let seen = self.seen_by_player(game);
+        let fighter = match self.fighter.as_mut() {
+            Some(fighter) => fighter,
+            None => return,
+        };
+
+        if let Some(opposite) = kind.opposite() {
+            if fighter.effects.remove(opposite).is_some() {
+                if seen {
+                    game.messages.add(format!("{} {}", self.name, opposite.end_message()), LIGHT_GREY);
+                }
+                return;
+            }
+        }
+
+        fighter.effects.apply(StatusEffect {
+            kind: kind,
+            turns: turns,
+            potency: potency,
+        });
+        if seen {
+            game.messages.add(format!("{} {}", self.name, kind.start_message()), kind.color());
+        }
+    }
+
+    // How many times this character acts in a turn.
+    // Hasted characters act twice every other turn, and slowed characters skip every other turn.
+    pub fn actions_per_turn(&self) -> i32 {
+        let effects = match self.fighter {
+            Some(fighter) => fighter.effects,
+            None => return 1,
+        };
+
+        if effects.get(EffectKind::Haste).map_or(false, |e| e.turns % 2 == 0) {
+            2
+        } else if effects.get(EffectKind::Slow).map_or(false, |e| e.turns % 2 == 0) {
+            0
+        } else {
+            1
+        }
+    }
+
+    // Runs a single turn of effects on this character, and counts them all down.
+    // Returns the damage the effects deal, after resistances, for the caller to apply.
+    // Only effects on characters the player can see are reported.
+    fn tick_effects(&mut self, game: &mut Game) -> Vec<(i32, DamageType)> {
+        let effects = match self.fighter {
+            Some(fighter) => fighter.effects,
+            None => return vec![],
+        };
+        let seen = self.seen_by_player(game);
+
+        let mut damage = vec![];
+        if let Some(poison) = effects.get(EffectKind::Poison) {
+            let poison_damage = self.resisted_damage(poison.potency, DamageType::Nature);
+            if seen {
+                game.messages.add(format!("{} suffers {} poison damage.", self.name, poison_damage), LIME);
+            }
+            damage.push((poison_damage, DamageType::Nature));
+        }
+        if let Some(burning) = effects.get(EffectKind::Burning) {
+            let burn_damage = self.resisted_damage(burning.potency, DamageType::Fire);
+            if seen {
+                game.messages.add(format!("{} burns for {} damage.", self.name, burn_damage), FLAME);
+            }
+            damage.push((burn_damage, DamageType::Fire));
+        }
+        if let Some(regeneration) = effects.get(EffectKind::Regeneration) {
+            self.heal(regeneration.potency);
+        }
+
+        let expired = self.fighter.as_mut().map_or(vec![], |f| f.effects.tick());
+        for kind in expired.into_iter().filter(|_| seen) {
+            game.messages.add(format!("{} {}", self.name, kind.end_message()), LIGHT_GREY);
+        }
+        damage
+    }
+
+    // Runs a turn of effects on every character, including the player.
+    // Monsters killed by an effect still award experience to the player.
+    pub fn tick_all_effects(game: &mut Game, characters: &mut [Character], player: &mut Object) {
+        for character in characters.iter_mut() {
+            for (damage, kind) in character.object.tick_effects(game) {
+                if let Some(exp) = character.object.take_damage(damage, kind, game) {
+                    player.fighter.as_mut().map(|f| f.exp += exp);
+                }
+            }
+        }
+
+        for (damage, kind) in player.tick_effects(game) {
+            if player.alive {
+                Object::player_damage(damage, kind, game, player);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(kind: EffectKind, turns: i32, potency: i32) -> StatusEffect {
+        StatusEffect {
+            kind: kind,
+            turns: turns,
+            potency: potency,
+        }
+    }
+
+    #[test]
+    fn refresh_keeps_the_stronger_of_each() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(EffectKind::Burning, 5, 1));
+        effects.apply(effect(EffectKind::Burning, 3, 4));
+        assert_eq!(effects.get(EffectKind::Burning), Some(effect(EffectKind::Burning, 5, 4)));
+    }
+
+    #[test]
+    fn intensify_adds_potencies() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(EffectKind::Poison, 5, 2));
+        effects.apply(effect(EffectKind::Poison, 3, 3));
+        assert_eq!(effects.get(EffectKind::Poison), Some(effect(EffectKind::Poison, 5, 5)));
+    }
+
+    #[test]
+    fn extend_adds_durations() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(EffectKind::Confusion, 5, 0));
+        effects.apply(effect(EffectKind::Confusion, 3, 0));
+        assert_eq!(effects.get(EffectKind::Confusion), Some(effect(EffectKind::Confusion, 8, 0)));
+    }
+
+    #[test]
+    fn tick_expires_effects_which_run_out() {
+        let mut effects = StatusEffects::default();
+        effects.apply(effect(EffectKind::Fear, 1, 0));
+        effects.apply(effect(EffectKind::Blindness, 2, 0));
+
+        assert_eq!(effects.tick(), vec![EffectKind::Fear]);
+        assert_eq!(effects.active(), vec![effect(EffectKind::Blindness, 1, 0)]);
+        assert_eq!(effects.tick(), vec![EffectKind::Blindness]);
+        assert!(effects.active().is_empty());
+    }
+
+    #[test]
+    fn haste_and_slow_cancel_out() {
+        let mut game = Game::for_tests();
+        let mut player = Object::new_player().object;
+
+        player.add_effect(EffectKind::Haste, 10, 0, &mut game);
+        player.add_effect(EffectKind::Slow, 10, 0, &mut game);
+        assert!(!player.has_effect(EffectKind::Haste));
+        assert!(!player.has_effect(EffectKind::Slow));
+
+        player.add_effect(EffectKind::Slow, 10, 0, &mut game);
+        assert!(player.has_effect(EffectKind::Slow));
+    }
+}
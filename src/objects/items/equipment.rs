@@ -0,0 +1,161 @@
+use super::*;
+
+use rand::Rng;
+
+// Chance of a piece of equipment carrying an element, and how strongly elemental armor and trinkets resist it.
+const ELEMENTAL_CHANCE: f32 = 0.25;
+const ARMOR_RESISTANCE: i32 = 25;
+const TRINKET_RESISTANCE: i32 = 40;
+
+// Every element equipment can carry.
+const ELEMENTS: [DamageType; 7] = [
+    DamageType::Fire,
+    DamageType::Water,
+    DamageType::Earth,
+    DamageType::Light,
+    DamageType::Death,
+    DamageType::Crystal,
+    DamageType::Nature,
+];
+
+// The places equipment can be worn. The player can only wear one piece in each.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Slot {
+    Weapon,
+    Armor,
+    Trinket,
+}
+
+impl Slot {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Slot::Weapon => "Weapon",
+            Slot::Armor => "Armor",
+            Slot::Trinket => "Trinket",
+        }
+    }
+}
+
+// Equipment definition.
+// Bonuses are added to the stats of the player while it's worn.
+// Elemental weapons deal their element in place of a plain blow, and elemental armor and trinkets resist it.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Equipment {
+    pub slot: Slot,
+    #[serde(default)]
+    pub power: i32,
+    #[serde(default)]
+    pub defense: i32,
+    #[serde(default)]
+    pub max_hp: i32,
+    #[serde(default)]
+    pub accuracy: i32,
+    #[serde(default)]
+    pub evasion: i32,
+    #[serde(default)]
+    pub element: Option<DamageType>,
+    #[serde(default)]
+    pub resistance: Option<(DamageType, i32)>,
+}
+
+// Gives a piece of equipment a random element, or leaves it as it is.
+// Deeper levels are more likely to hold elemental equipment. Returns the equipment, and its new name.
+pub fn roll_element(mut equipment: Equipment, name: &str, level: u32) -> (Equipment, String) {
+    let mut rng = rand::thread_rng();
+    let elemental_chance = ELEMENTAL_CHANCE + level as f32 * 0.02;
+
+    if rng.gen::<f32>() >= elemental_chance {
+        return (equipment, name.to_string());
+    }
+
+    let element = ELEMENTS[rng.gen_range(0, ELEMENTS.len())];
+    match equipment.slot {
+        Slot::Weapon => equipment.element = Some(element),
+        Slot::Armor => equipment.resistance = Some((element, ARMOR_RESISTANCE)),
+        Slot::Trinket => equipment.resistance = Some((element, TRINKET_RESISTANCE)),
+    }
+    (equipment, format!("{} of {}", name, element.name()))
+}
+
+impl Equipment {
+    // Describes the bonuses of the equipment, such as "+4 attack, -5 accuracy, fire damage".
+    pub fn description(&self) -> String {
+        let mut bonuses = vec![];
+        for (amount, stat) in [
+            (self.power, "attack"),
+            (self.defense, "defense"),
+            (self.max_hp, "HP"),
+            (self.accuracy, "accuracy"),
+            (self.evasion, "evasion"),
+        ].iter() {
+            if *amount != 0 {
+                bonuses.push(format!("{:+} {}", amount, stat));
+            }
+        }
+        if let Some(element) = self.element {
+            bonuses.push(format!("{} damage", element.name()));
+        }
+        if let Some((element, amount)) = self.resistance {
+            bonuses.push(format!("{} {}%", element.name(), amount));
+        }
+        bonuses.join(", ")
+    }
+}
+
+impl Object {
+    // Puts on a piece of equipment, adding its bonuses to the player's stats.
+    // Returns whatever was already worn in the same slot, which is taken off to make room.
+    pub fn equip(item: Object, game: &mut Game, player: &mut Object) -> Option<Object> {
+        let equipment = match item.equipment {
+            Some(equipment) => equipment,
+            None => return Some(item),
+        };
+
+        let worn = game.equipment.iter().position(|worn| worn.equipment.map_or(false, |e| e.slot == equipment.slot));
+        let removed = worn.and_then(|index| Object::unequip(index, game, player));
+
+        game.messages.add(format!("You equip the {}.", item.name), LIGHT_GREEN);
+        Object::apply_equipment(&equipment, 1, player);
+        game.equipment.push(item);
+        Object::refresh_resistances(game, player);
+        removed
+    }
+
+    // Takes off a piece of equipment, removing its bonuses from the player's stats.
+    pub fn unequip(index: usize, game: &mut Game, player: &mut Object) -> Option<Object> {
+        if index >= game.equipment.len() {
+            return None;
+        }
+
+        let item = game.equipment.remove(index);
+        if let Some(equipment) = item.equipment {
+            Object::apply_equipment(&equipment, -1, player);
+        }
+        game.messages.add(format!("You take off the {}.", item.name), LIGHT_GREY);
+        Object::refresh_resistances(game, player);
+        Some(item)
+    }
+
+    // Adds the bonuses of a piece of equipment to the player's stats, or takes them away again.
+    // Taking off equipment never leaves the player with more health than they can hold, or none at all.
+    fn apply_equipment(equipment: &Equipment, sign: i32, player: &mut Object) {
+        if let Some(fighter) = player.fighter.as_mut() {
+            fighter.power += sign * equipment.power;
+            fighter.defense += sign * equipment.defense;
+            fighter.max_hp += sign * equipment.max_hp;
+            fighter.accuracy += sign * equipment.accuracy;
+            fighter.evasion += sign * equipment.evasion;
+            fighter.hp = fighter.hp.min(fighter.max_hp).max(1);
+        }
+    }
+
+    // Returns the element of the player's weapon, or a plain blow if it doesn't have one.
+    pub fn weapon_element(game: &Game) -> DamageType {
+        game.equipment
+            .iter()
+            .filter_map(|item| item.equipment)
+            .find(|equipment| equipment.slot == Slot::Weapon)
+            .and_then(|equipment| equipment.element)
+            .unwrap_or(DamageType::Physical)
+    }
+}
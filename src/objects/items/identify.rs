@@ -0,0 +1,275 @@
+use super::*;
+use super::table::ItemTable;
+
+use rand::Rng;
+
+// Descriptions given to unidentified potions.
+const POTION_LOOKS: [&str; 12] = [
+    "murky", "bubbling", "smoky", "fizzy", "cloudy", "glowing",
+    "viscous", "swirling", "oily", "milky", "sparkling", "foul-smelling",
+];
+
+// Tints added to the descriptions of potions once every plain one is taken, such as "murky amber potion".
+const POTION_TINTS: [&str; 8] = ["amber", "crimson", "golden", "inky", "pearly", "silvery", "verdant", "violet"];
+
+// Syllables which are strung together into the labels of unidentified scrolls.
+const SCROLL_SYLLABLES: [&str; 16] = [
+    "xy", "zzy", "foo", "bie", "blet", "ch", "ka", "lam",
+    "ve", "nor", "tha", "pri", "rzo", "elb", "ib", "mur",
+];
+
+// Colors given to unidentified potions and scrolls.
+const LOOK_COLORS: [Color; 12] = [
+    LIGHT_RED, LIGHT_ORANGE, LIGHT_YELLOW, LIGHT_LIME, LIGHT_GREEN, LIGHT_CYAN,
+    LIGHT_AZURE, LIGHT_BLUE, LIGHT_VIOLET, LIGHT_MAGENTA, LIGHT_PINK, LIGHT_SEPIA,
+];
+
+// How an unidentified kind of item looks during this run.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Appearance {
+    pub kind: String,
+    pub category: Category,
+    pub name: String,
+    pub color: Color,
+}
+
+// Keeps track of what each kind of potion and scroll looks like, and which of them the player has figured out.
+// Guesses are names the player has given to kinds they haven't identified yet.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Identification {
+    pub appearances: Vec<Appearance>,
+    pub known: Vec<String>,
+    pub guesses: Vec<(String, String)>,
+}
+
+// Makes up a scroll label, such as "XYZZY" or "FOO BLETCH".
+fn scroll_label(rng: &mut impl Rng) -> String {
+    let words = rng.gen_range(1, 3);
+    (0..words)
+        .map(|_| {
+            let syllables = rng.gen_range(2, 4);
+            (0..syllables)
+                .map(|_| SCROLL_SYLLABLES[rng.gen_range(0, SCROLL_SYLLABLES.len())])
+                .collect::<String>()
+                .to_uppercase()
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+impl Identification {
+    // Shuffles the appearances of every potion and scroll in the item table, for a new run.
+    pub fn new(table: &ItemTable) -> Identification {
+        let mut rng = rand::thread_rng();
+        let mut potion_colors = LOOK_COLORS.to_vec();
+        let mut scroll_colors = LOOK_COLORS.to_vec();
+        rng.shuffle(&mut potion_colors);
+        rng.shuffle(&mut scroll_colors);
+
+        // Plain descriptions are handed out first, and tinted ones after, so no two kinds of potion look alike.
+        let mut looks: Vec<String> = POTION_LOOKS.iter().map(|look| look.to_string()).collect();
+        let mut tinted: Vec<String> = POTION_TINTS
+            .iter()
+            .flat_map(|tint| POTION_LOOKS.iter().map(move |look| format!("{} {}", look, tint)))
+            .collect();
+        rng.shuffle(&mut looks);
+        rng.shuffle(&mut tinted);
+        looks.extend(tinted);
+
+        let mut appearances: Vec<Appearance> = vec![];
+        for def in table.items.iter() {
+            let (name, color) = match def.item.category {
+                Category::Potion => {
+                    let count = appearances.iter().filter(|a| a.category == Category::Potion).count();
+                    let look = looks.get(count).expect("There are more kinds of potion than potion descriptions");
+                    (format!("{} potion", look), potion_colors[count % potion_colors.len()])
+                },
+                Category::Scroll => {
+                    let count = appearances.iter().filter(|a| a.category == Category::Scroll).count();
+                    // Labels are made up, so they are rerolled until they are all different.
+                    let mut label = scroll_label(&mut rng);
+                    while appearances.iter().any(|a| a.name == format!("scroll labeled {}", label)) {
+                        label = scroll_label(&mut rng);
+                    }
+                    (format!("scroll labeled {}", label), scroll_colors[count % scroll_colors.len()])
+                },
+                _ => continue,
+            };
+            appearances.push(Appearance {
+                kind: def.name.clone(),
+                category: def.item.category,
+                name: name,
+                color: color,
+            });
+        }
+
+        Identification {
+            appearances: appearances,
+            known: vec![],
+            guesses: vec![],
+        }
+    }
+
+    fn appearance(&self, kind: &str) -> Option<&Appearance> {
+        self.appearances.iter().find(|a| a.kind == kind)
+    }
+
+    // Checks if the player knows what a kind of item is. Anything without an appearance is always known.
+    pub fn is_known(&self, kind: &str) -> bool {
+        self.appearance(kind).is_none() || self.known.iter().any(|known| known == kind)
+    }
+
+    // The name the player sees for a kind of item.
+    // Unidentified items go by their appearance, alongside whatever the player has guessed they are.
+    pub fn display_name(&self, kind: &str) -> String {
+        match self.appearance(kind) {
+            Some(appearance) if !self.is_known(kind) => {
+                match self.guesses.iter().find(|(guessed, _)| guessed == kind) {
+                    Some((_, guess)) => format!("{} (called {})", appearance.name, guess),
+                    None => appearance.name.clone(),
+                }
+            },
+            _ => kind.to_string(),
+        }
+    }
+
+    pub fn name_of(&self, object: &Object) -> String {
+        if object.item.is_some() {
+            self.display_name(&object.name)
+        } else {
+            object.name.clone()
+        }
+    }
+
+    // Gives an item the color of its appearance.
+    // The color is kept once the item is identified, as it still looks the same.
+    pub fn disguise(&self, object: &mut Object) {
+        if let Some(appearance) = self.appearance(&object.name) {
+            object.color = appearance.color;
+        }
+    }
+
+    // Marks a kind of item as identified. Returns true if the player didn't know it already.
+    pub fn identify(&mut self, kind: &str) -> bool {
+        if self.is_known(kind) {
+            return false;
+        }
+        self.known.push(kind.to_string());
+        self.guesses.retain(|(guessed, _)| guessed != kind);
+        true
+    }
+
+    // Records what the player thinks a kind of item is.
+    pub fn guess(&mut self, kind: &str, guess: &str) {
+        self.guesses.retain(|(guessed, _)| guessed != kind);
+        self.guesses.push((kind.to_string(), guess.to_string()));
+    }
+
+    // Every kind of item the player could still be looking at, given its category.
+    pub fn candidates(&self, category: Category) -> Vec<String> {
+        self.appearances
+            .iter()
+            .filter(|a| a.category == category && !self.is_known(&a.kind))
+            .map(|a| a.kind.clone())
+            .collect()
+    }
+}
+
+impl Object {
+    // Identifies the kind of an item the player has just used, telling them what it was.
+    pub fn learn_item(kind: &str, game: &mut Game) {
+        let appearance = game.identity.appearance(kind).map_or(kind.to_string(), |a| a.name.clone());
+        if game.identity.identify(kind) {
+            game.messages.add(format!("You now know the {} is a {}.", appearance, kind), WHITE);
+        }
+    }
+
+    // Identifies up to a number of unknown kinds of item in the player's inventory, picked at random.
+    pub fn identify_carried(count: i32, inventory: &[Object], game: &mut Game) {
+        let mut unknown: Vec<String> = vec![];
+        for item in inventory.iter().filter(|item| !game.identity.is_known(&item.name)) {
+            if !unknown.contains(&item.name) {
+                unknown.push(item.name.clone());
+            }
+        }
+        if unknown.is_empty() {
+            game.messages.add("You already know everything you are carrying.", WHITE);
+            return;
+        }
+
+        rand::thread_rng().shuffle(&mut unknown);
+        for kind in unknown.iter().take(count.max(1) as usize) {
+            Object::learn_item(kind, game);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A table holding the given number of copies of the first potion and scroll of the item table.
+    fn table_of(potions: usize, scrolls: usize) -> ItemTable {
+        let table = ItemTable::built_in();
+        let potion = table.items.iter().find(|def| def.item.category == Category::Potion).unwrap();
+        let scroll = table.items.iter().find(|def| def.item.category == Category::Scroll).unwrap();
+
+        let mut items = vec![];
+        for (def, count) in [(potion, potions), (scroll, scrolls)].iter() {
+            for i in 0..*count {
+                let mut copy = (*def).clone();
+                copy.name = format!("{} {}", def.name, i);
+                items.push(copy);
+            }
+        }
+        ItemTable { items: items }
+    }
+
+    fn assert_distinct(identity: &Identification) {
+        for (i, a) in identity.appearances.iter().enumerate() {
+            for b in identity.appearances.iter().skip(i + 1) {
+                assert_ne!(a.name, b.name, "{} and {} look the same", a.kind, b.kind);
+            }
+        }
+    }
+
+    #[test]
+    fn every_potion_and_scroll_has_an_appearance() {
+        let table = ItemTable::built_in();
+        let identity = Identification::new(&table);
+        for def in table.items.iter() {
+            let disguised = def.item.category == Category::Potion || def.item.category == Category::Scroll;
+            assert_eq!(identity.appearance(&def.name).is_some(), disguised, "{}", def.name);
+        }
+    }
+
+    #[test]
+    fn appearances_are_distinct() {
+        assert_distinct(&Identification::new(&ItemTable::built_in()));
+    }
+
+    #[test]
+    fn appearances_are_distinct_with_every_potion_look_used() {
+        // Scroll labels are random, so a few tables are tried to give clashes a chance to happen.
+        for _ in 0..50 {
+            assert_distinct(&Identification::new(&table_of(POTION_LOOKS.len(), 20)));
+        }
+    }
+
+    #[test]
+    fn appearances_are_distinct_with_more_potions_than_looks() {
+        let identity = Identification::new(&table_of(POTION_LOOKS.len() * 3, 0));
+        assert_eq!(identity.appearances.len(), POTION_LOOKS.len() * 3);
+        assert_distinct(&identity);
+    }
+
+    #[test]
+    fn new_kinds_start_unknown() {
+        let table = ItemTable::built_in();
+        let identity = Identification::new(&table);
+        for appearance in identity.appearances.iter() {
+            assert!(!identity.is_known(&appearance.kind));
+            assert_eq!(identity.display_name(&appearance.kind), appearance.name);
+        }
+    }
+}
@@ -3,23 +3,81 @@ use crate::environment::Game;
 use crate::graphics::gui::target_tile;
 
 use super::{ Object, Character };
-use crate::objects::npc::ai::Ai;
+use crate::objects::effects::EffectKind;
+use crate::objects::npc::DamageType;
+use crate::objects::combat::{ Attack, resolve };
 
 use std::collections::HashMap;
 use tcod::colors::*;
 
 use serde::{ Serialize, Deserialize };
 
+pub mod monsters;
+pub mod equipment;
+pub mod table;
+pub mod identify;
+
+// The broad kinds of item, which decide where they are found, and how they are described.
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
-pub enum Item {
-    Heal,
-    LightningBoltScroll,
-    ConfusionScroll,
-    FireballScroll,
-    FearScroll,
-    HpUp,
-    PowUp,
-    DefUp,
+pub enum Category {
+    Potion,
+    Scroll,
+    Food,
+    Equipment,
+}
+
+impl Category {
+    // How a monster is described using an item of this kind, such as "The lizard reads a ...".
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Category::Potion => "drinks",
+            Category::Scroll => "reads",
+            Category::Food => "eats",
+            Category::Equipment => "equips",
+        }
+    }
+}
+
+// Who an item affects when it is used.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Targeting {
+    User,                                     // Whoever uses the item.
+    Closest { range: i32 },                   // The closest visible enemy.
+    Tile { range: i32 },                      // Whoever stands on a chosen tile.
+    Area { range: Option<i32>, radius: f32 }, // Everyone around a chosen tile, the user included.
+}
+
+// The stats which food can permanently raise.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Stat {
+    MaxHp,
+    Power,
+    Defense,
+    Accuracy,
+    Evasion,
+}
+
+// The building blocks every item is made from.
+// An item applies each of its effects, in order, to each of its targets.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ItemEffect {
+    Heal { amount: i32 },
+    Damage { amount: i32, element: DamageType },
+    Status { kind: EffectKind, turns: i32, potency: i32 },
+    Buff { stat: Stat, amount: i32, per_levels: u32 }, // Permanent, and grows every so many levels.
+    RevealTraps { radius: f32 },
+    Identify { count: i32 }, // Identifies kinds of item the player is carrying.
+    Equip,
+}
+
+// Item definition. Items are described in the item table, rather than in code.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    pub category: Category,
+    pub targeting: Targeting,
+    pub effects: Vec<ItemEffect>,
+    #[serde(default)]
+    pub message: Option<String>,
 }
 
 // Used to determine what happens to an item after it is used.
@@ -27,6 +85,31 @@ pub enum Item {
 pub enum UseResult {
     UsedUp,
     Cancelled,
+    Equip, // Item is moved from the inventory, on to the player.
+}
+
+// Someone using an item, or affected by one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Player,
+    Monster(usize),
+}
+
+impl Item {
+    // Checks if the item does anything besides healing, so it isn't wasted on someone at full health.
+    pub fn only_heals(&self) -> bool {
+        self.effects.iter().all(|effect| match effect {
+            ItemEffect::Heal { .. } => true,
+            _ => false,
+        })
+    }
+
+    pub fn heals(&self) -> bool {
+        self.effects.iter().any(|effect| match effect {
+            ItemEffect::Heal { .. } => true,
+            _ => false,
+        })
+    }
 }
 
 impl Object {
@@ -44,360 +127,204 @@ impl Object {
             fighter: None,
             ai: None,
             item: None,
+            trap: None,
+            equipment: None,
             level: 1,
             always_visible: true,
         }
     }
 
-    // Health Potion constructor.
-    pub fn health_pot(x: i32, y: i32) -> Object {
-        let mut health_pot = Object::new_item(x, y, '!', "Health potion", LIGHT_GREEN, false);
-        health_pot.item = Some(Item::Heal);
-        health_pot
-    }
-    // Health potion use function.
-    pub fn use_health_potion(
-        _inventory_id: usize,
-        _tcod: &mut Tcod,
-        game: &mut Game,
-        player: &mut Object,
-        _characters: &mut Vec<Character>,
-        _items: &mut HashMap<i32, Object>
-    ) -> UseResult {
-        // Establish the healing value of the item.
-        let heal_amount = 40;
-        // Accesses the fighter component of the player.
-        if let Some(fighter) = player.fighter {
-            // If fighter hp is at max, the item usage is cancelled.
-            if fighter.hp == fighter.max_hp {
-                game.messages.add("You are already at full health.", RED);
-                return UseResult::Cancelled;
-            }
-            // Otherwise, the player is healed by the heal amount.
-            game.messages.add("Your wounds start to feel better!", LIGHT_GREEN);
-            player.heal(heal_amount);
-            return UseResult::UsedUp;
-        }
-        // If the players fighter component is inaccessible, the item usage is cancelled.
-        UseResult::Cancelled
-    }
-
-    // Lightning bolt scroll creator.
-    pub fn lightning_bolt_scroll(x: i32, y: i32) -> Object {
-        let mut lightning_bolt_scroll = Object::new_item(x, y, '#', "Scroll of lightning bolt", LIGHT_YELLOW, false);
-        lightning_bolt_scroll.item = Some(Item::LightningBoltScroll);
-        lightning_bolt_scroll
-    }
-    // Lightning bolt scroll use function.
-    pub fn use_lightning_bolt_scroll(
-        _inventory_id: usize,
-        tcod: &mut Tcod,
-        game: &mut Game,
-        player: &mut Object,
-        characters: &mut Vec<Character>,
-        _items: &mut HashMap<i32, Object>
-    ) -> UseResult {
-        // Establish damage variables, range, and closest enemy within range.
-        let lightning_range = 5;
-        let lightning_damage = 40;
-        let monster_id = Object::closest_monster(player, tcod, characters, lightning_range);
-
-        // If monster is found, continue the item effect
-        if let Some(monster_id) = monster_id {
-            // Damge effect messages.
-            game.messages.add(
-                format!(
-                    "A lightning bolt strikes the {} with a loud thunder!",
-                    characters[monster_id].object.name
-                ),
-                LIGHT_CYAN,
-            );
-            game.messages.add(
-                format!(
-                    "The damage is {} hit points.",
-                    lightning_damage
-                ),
-                LIGHT_CYAN,
-            );
-            // Damage enemy, and give experience points to player if killed.
-            if let Some(exp) = characters[monster_id].object.take_damage(lightning_damage, game) {
-                player.fighter.as_mut().unwrap().exp += exp;
-            }
-            // Item is destroyed.
-            UseResult::UsedUp
-        // No monster is found in range.
-        } else {
-            // Displays message, and item usage is cancelled.
-            game.messages.add("No enemy is close enough to strike.", RED);
-            UseResult::Cancelled
+    // Finds the first free key in the item hashmap.
+    pub fn free_item_id(items: &HashMap<i32, Object>) -> i32 {
+        let mut new_id = 1;
+        while items.contains_key(&new_id) {
+            new_id += 1;
         }
+        new_id
     }
 
-    // Confusion scroll creator.
-    pub fn confusion_scroll(x: i32, y: i32) -> Object {
-        let mut confusion_scroll = Object::new_item(x, y, '#', "Scroll of confusion", LIGHT_HAN, false);
-        confusion_scroll.item = Some(Item::ConfusionScroll);
-        confusion_scroll
-    }
-    // Lightning bolt scroll use function.
-    pub fn use_confusion_scroll(
-        _inventory_id: usize,
+    // Uses an item from the player's inventory, asking the player for a target if the item needs one.
+    // Items are referred to by the name the player knows them by, so prompts don't give unknown items away.
+    pub fn use_item_effects(
+        item: &Item,
+        name: &str,
+        color: Color,
         tcod: &mut Tcod,
         game: &mut Game,
         player: &mut Object,
         characters: &mut Vec<Character>,
         items: &mut HashMap<i32, Object>
     ) -> UseResult {
-        // Set up spell variables.
-        let confuse_range = 8;
-        let confuse_num_turns = 10;
-        // Asks the player to select an enemy to confuse.
-        game.messages.add(
-            "Left-click an enemy to confuse them, or right-click to cancel...",
-            LIGHTER_HAN,
-        );
-        // Checks to see that the tile which the player clicks on is in the specified range
-        let (x, y) = match target_tile(tcod, game, characters, items, player, Some(confuse_range as f32)) {
-            Some(tile_pos) => tile_pos,
-            // If not in range, the item usage is cancelled.
-            None => {
-                game.messages.add("Nothing happens...", RED);
-                return UseResult::Cancelled;
-            }
-        };
+        // Healing items are saved for when they're needed, once the player knows what they are.
+        let known = game.identity.is_known(name);
+        let name = game.identity.display_name(name);
+        if known && item.only_heals() && player.fighter.map_or(false, |f| f.hp == f.max_hp) {
+            game.messages.add("You are already at full health.", RED);
+            return UseResult::Cancelled;
+        }
 
-        // Character vector is searched through.
-        for cha in characters {
-            // If character position matches the tile which was clicked on, the item usage happens.
-            if cha.object.pos() == (x, y) {
-                // Removes the AI of the monster to be inserted into the "confused AI" state.
-                // This is done, so that the confused state knows which AI to return to.
-                let old_ai = cha.object.ai.take().unwrap_or(Ai::Basic); // If this fails, it defaults to Basic AI.
-                // Replace the monster's AI with a "confused" state.
-                // After some time, returns to previous AI.
-                cha.object.ai = Some(Ai::Confused {
-                    previous_ai: Box::new(old_ai),
-                    num_turns: confuse_num_turns,
-                });
-                // Displays a message showing that the monster has become confused.
+        let targets = match item.targeting {
+            Targeting::User => vec![Target::Player],
+            Targeting::Closest { range } => match Object::closest_monster(player, tcod, characters, range) {
+                Some(monster_id) => vec![Target::Monster(monster_id)],
+                None => {
+                    game.messages.add("No enemy is close enough to strike.", RED);
+                    return UseResult::Cancelled;
+                }
+            },
+            Targeting::Tile { range } => {
                 game.messages.add(
-                    format!(
-                        "The eyes of {} appear vacant, as it begins to stumble around!",
-                        cha.object.name
-                    ),
-                    LIGHTER_HAN,
+                    format!("Left-click an enemy to target with the {}, or right-click to cancel...", name),
+                    color,
                 );
-            }
-        }
-        // Item is used up, and removed from the inventory.
-        UseResult::UsedUp
-    }
-
-    // Fireball scroll creator.
-    pub fn fireball_scroll(x: i32, y: i32) -> Object {
-        let mut fireball_scroll = Object::new_item(x, y, '#', "Scroll of Fireball", FLAME, false);
-        fireball_scroll.item = Some(Item::FireballScroll);
-        fireball_scroll
-    }
-    // Fireball scroll use function.
-    pub fn use_fireball_scroll(
-        _inventory_id: usize,
-        tcod: &mut Tcod,
-        game: &mut Game,
-        player: &mut Object,
-        characters: &mut Vec<Character>,
-        items: &mut HashMap<i32, Object>
-    ) -> UseResult {
-        // Set up spell variables.
-        let fireball_radius = 3;
-        let fireball_damage = 25;
-
-        // Ask the player for a target tile to throw the fireball at.
-        game.messages.add(
-            "Left-click a target tile for the fireball, or right-click to cancel...",
-            LIGHTER_FLAME,
-        );
-        // A check is done to ensure the player clicked within the right radius.
-        let (x, y) = match target_tile(tcod, game, characters, items, player, None) {
-            Some(tile_pos) => tile_pos,
-            // If outside the radius, item usage is cancelled.
-            None => return UseResult::Cancelled,
-        };
-        // Explosion message is stated.
-        game.messages.add(
-            format!(
-                "The fireball explodes, burning everything within {} tiles!",
-                fireball_radius
-            ),
-            FLAME,
-        );
-
-        // Establish variable to track exp to give to player.
-        let mut exp_to_gain = 0;
-
-        // Searches through character vector
-        for cha in characters {
-            // If character is within the radius of the explosion, the item effect happens to them.
-            if cha.object.distance(x, y) <= fireball_radius as f32 && cha.object.fighter.is_some() {
-                // Message to show that the relevant character was damaged.
+                match target_tile(tcod, game, characters, items, player, Some(range as f32)) {
+                    Some((x, y)) => Object::targets_around(x, y, 0.0, characters, player),
+                    None => {
+                        game.messages.add("Nothing happens...", RED);
+                        return UseResult::Cancelled;
+                    }
+                }
+            },
+            Targeting::Area { range, radius } => {
                 game.messages.add(
-                    format!(
-                        "The {} us burned for {} hit points!",
-                        cha.object.name, fireball_damage
-                    ),
-                    FLAME,
+                    format!("Left-click a target tile for the {}, or right-click to cancel...", name),
+                    color,
                 );
-                // Damage enemy, and aggregate experience points.
-                if let Some(exp) = cha.object.take_damage(fireball_damage, game) {
-                    exp_to_gain += exp;
+                match target_tile(tcod, game, characters, items, player, range.map(|range| range as f32)) {
+                    Some((x, y)) => Object::targets_around(x, y, radius, characters, player),
+                    None => return UseResult::Cancelled,
                 }
-            }
-        }
-
-        // Give experience points to player.
-        player.fighter.as_mut().unwrap().exp += exp_to_gain;
+            },
+        };
 
-        // Also damages player, if in range.
-        if player.distance(x, y) <= fireball_radius as f32 {
-            game.messages.add(
-                format!(
-                    "You were unable to avoid the flames, and took {} damage...",
-                    fireball_damage
-                ),
-                DARK_FLAME,
-            );
-            Object::player_damage(fireball_damage, game, player);
+        if let Some(message) = &item.message {
+            game.messages.add(message.clone(), color);
         }
-        // Item is used up, and removed from inventory.
-        UseResult::UsedUp
+        Object::apply_item(item, color, Target::Player, &targets, game, characters, player, Some(items))
     }
 
-    // Fear scroll creator.
-    pub fn fear_scroll(x: i32, y: i32) -> Object {
-        let mut fear_scroll = Object::new_item(x, y, '#', "Scroll of Fear", DARKER_PURPLE, false);
-        fear_scroll.item = Some(Item::FearScroll);
-        fear_scroll
+    // Finds everyone standing within a radius of a tile, the player included.
+    pub fn targets_around(x: i32, y: i32, radius: f32, characters: &[Character], player: &Object) -> Vec<Target> {
+        let mut targets: Vec<Target> = characters
+            .iter()
+            .enumerate()
+            .filter(|(_, character)| character.object.fighter.is_some() && character.object.distance(x, y) <= radius)
+            .map(|(id, _)| Target::Monster(id))
+            .collect();
+        if player.alive && player.distance(x, y) <= radius {
+            targets.push(Target::Player);
+        }
+        targets
     }
-    // Fear scroll use function.
-    pub fn use_fear_scroll(
-        _inventory_id: usize,
-        tcod: &mut Tcod,
-        game: &mut Game,
-        player: &mut Object,
-        characters: &mut Vec<Character>,
-        items: &mut HashMap<i32, Object>
-    ) -> UseResult {
-        // Set up spell variables.
-        let fear_range = 8;
-        let fear_num_turns = 10;
-        // Asks the player to select an enemy to confuse.
-        game.messages.add(
-            "Left-click an enemy to instill with fear, or right-click to cancel...",
-            DARKER_PURPLE,
-        );
-        // Checks to see that the tile which the player clicks on is in the specified range
-        let (x, y) = match target_tile(tcod, game, characters, items, player, Some(fear_range as f32)) {
-            Some(tile_pos) => tile_pos,
-            // If not in range, the item usage is cancelled.
-            None => {
-                game.messages.add("Nothing happens...", RED);
-                return UseResult::Cancelled;
-            }
-        };
 
-        // Character vector is searched through.
-        for cha in characters {
-            // If character position matches the tile which was clicked on, the item usage happens.
-            if cha.object.pos() == (x, y) {
-                // Removes the AI of the monster to be inserted into the "confused AI" state.
-                // This is done, so that the confused state knows which AI to return to.
-                let old_ai = cha.object.ai.take().unwrap_or(Ai::Basic); // If this fails, it defaults to Basic AI.
-                // Replace the monster's AI with a "confused" state.
-                // After some time, returns to previous AI.
-                cha.object.ai = Some(Ai::Fear {
-                    previous_ai: Box::new(old_ai),
-                    num_turns: fear_num_turns,
-                });
-                // Displays a message showing that the monster has become confused.
-                game.messages.add(
-                    format!(
-                        "The eyes of {} grow wide, as it freezes in terror!",
-                        cha.object.name
-                    ),
-                    DARKER_PURPLE,
-                );
-            }
+    // Looks up the object behind a target.
+    fn target_object<'a>(target: Target, characters: &'a mut [Character], player: &'a mut Object) -> &'a mut Object {
+        match target {
+            Target::Player => player,
+            Target::Monster(monster_id) => &mut characters[monster_id].object,
         }
-        // Item is used up, and removed from the inventory.
-        UseResult::UsedUp
     }
 
-    pub fn health_up(x: i32, y: i32) -> Object {
-        let mut health_up = Object::new_item(x, y, '/', "Kale", LIGHTER_LIME, false);
-        health_up.item = Some(Item::HpUp);
-        health_up
-    }
-    // Health up use function.
-    pub fn use_health_up(
-        _inventory_id: usize,
-        _tcod: &mut Tcod,
+    // Applies every effect of an item to each of its targets, whether the player or a monster used it.
+    // Crystalline monsters turn spells aside, sending any damage back at whoever used the item.
+    // Trap reveals need the item map, so they do nothing without it.
+    pub fn apply_item(
+        item: &Item,
+        color: Color,
+        user: Target,
+        targets: &[Target],
         game: &mut Game,
+        characters: &mut [Character],
         player: &mut Object,
-        _characters: &mut Vec<Character>,
-        _items: &mut HashMap<i32, Object>
+        mut items: Option<&mut HashMap<i32, Object>>
     ) -> UseResult {
-        // Buff the player
-        if let Some(ref mut fighter) = player.fighter {
-            game.messages.add("You eat the kale, and immediately feel healthier.", LIGHTEST_LIME);
-            fighter.max_hp += 5 * (game.dungeon_level / 5) as i32;
-            return UseResult::UsedUp;
+        if item.effects.contains(&ItemEffect::Equip) {
+            return UseResult::Equip;
         }
-        UseResult::Cancelled
-    }
 
-    pub fn power_up(x: i32, y: i32) -> Object {
-        let mut power_up = Object::new_item(x, y, '+', "Creatine Powder", LIGHT_CRIMSON, false);
-        power_up.item = Some(Item::PowUp);
-        power_up
-    }
-    // Power up use function.
-    pub fn use_power_up(
-        _inventory_id: usize,
-        _tcod: &mut Tcod,
-        game: &mut Game,
-        player: &mut Object,
-        _characters: &mut Vec<Character>,
-        _items: &mut HashMap<i32, Object>
-    ) -> UseResult {
-        // Buff the player
-        if let Some(ref mut fighter) = player.fighter {
-            game.messages.add("You consume the creatine, and your shirt tears a little bit.", LIGHTER_CRIMSON);
-            fighter.power += 1 * (game.dungeon_level / 10) as i32;
-            return UseResult::UsedUp;
+        for &target in targets {
+            let reflected = item.targeting != Targeting::User
+                && target != user
+                && Object::target_object(target, characters, player).reflects_spell();
+            if reflected {
+                let name = Object::target_object(target, characters, player).name.clone();
+                game.messages.add(format!("The spell glances off the {}!", name), color);
+            }
+
+            for effect in &item.effects {
+                match *effect {
+                    ItemEffect::Damage { amount, element } => {
+                        let victim = if reflected { user } else { target };
+                        Object::item_damage(amount, element, color, user, victim, game, characters, player);
+                    },
+                    _ if reflected => {},
+                    ItemEffect::Heal { amount } => Object::target_object(target, characters, player).heal(amount),
+                    ItemEffect::Status { kind, turns, potency } => {
+                        Object::target_object(target, characters, player).add_effect(kind, turns, potency, game);
+                    },
+                    ItemEffect::Buff { stat, amount, per_levels } => {
+                        // Buffs grow with depth, but are never worth less than their base amount.
+                        let bonus = amount * (game.dungeon_level / per_levels.max(1)).max(1) as i32;
+                        if let Some(fighter) = Object::target_object(target, characters, player).fighter.as_mut() {
+                            match stat {
+                                Stat::MaxHp => fighter.max_hp += bonus,
+                                Stat::Power => fighter.power += bonus,
+                                Stat::Defense => fighter.defense += bonus,
+                                Stat::Accuracy => fighter.accuracy += bonus,
+                                Stat::Evasion => fighter.evasion += bonus,
+                            }
+                        }
+                    },
+                    ItemEffect::RevealTraps { radius } => {
+                        if let Some(items) = items.as_mut() {
+                            let (x, y) = Object::target_object(target, characters, player).pos();
+                            if !Object::reveal_traps(x, y, radius, 1.0, game, items) {
+                                game.messages.add("You sense no traps nearby.", WHITE);
+                            }
+                        }
+                    },
+                    // Identifying needs the player's inventory, so it's handled once the item is used up.
+                    ItemEffect::Identify { .. } | ItemEffect::Equip => {},
+                }
+            }
         }
-        UseResult::Cancelled
+        UseResult::UsedUp
     }
 
-    pub fn defense_up(x: i32, y: i32) -> Object {
-        let mut defense_up = Object::new_item(x, y, '~', "Quinoa", PURPLE, false);
-        defense_up.item = Some(Item::DefUp);
-        defense_up
-    }
-    // Defense up use function.
-    pub fn use_defense_up(
-        _inventory_id: usize,
-        _tcod: &mut Tcod,
+    // Deals the damage of an item to one of its targets, keeping the player's combat stats up to date.
+    // Monsters slain by an item award their experience to the player, whoever used it.
+    fn item_damage(
+        amount: i32,
+        element: DamageType,
+        color: Color,
+        user: Target,
+        victim: Target,
         game: &mut Game,
-        player: &mut Object,
-        _characters: &mut Vec<Character>,
-        _items: &mut HashMap<i32, Object>
-    ) -> UseResult {
-        // Buff the player
-        if let Some(ref mut fighter) = player.fighter {
-            game.messages.add("You eat the quinoa, and feel your energy strengthen.", LIGHT_PURPLE);
-            fighter.defense += 1 * (game.dungeon_level / 10) as i32;
-            return UseResult::UsedUp;
+        characters: &mut [Character],
+        player: &mut Object
+    ) {
+        let attack = Attack::flat(amount, element);
+        match victim {
+            Target::Player => {
+                let result = resolve(&attack, player);
+                game.stats.record_received(&result);
+                game.messages.add(format!("You take {} {} damage!", result.damage, element.name()), color);
+                Object::player_damage(result.damage, result.kind, game, player);
+            },
+            Target::Monster(monster_id) => {
+                let monster = &mut characters[monster_id].object;
+                let result = resolve(&attack, monster);
+                if user == Target::Player {
+                    game.stats.record_dealt(&result);
+                }
+                game.messages.add(
+                    format!("The {} takes {} {} damage!", monster.name, result.damage, element.name()),
+                    color,
+                );
+                if let Some(exp) = monster.take_damage(result.damage, result.kind, game) {
+                    player.fighter.as_mut().map(|f| f.exp += exp);
+                }
+            },
         }
-        UseResult::Cancelled
     }
 }
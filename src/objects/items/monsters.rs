@@ -0,0 +1,116 @@
+use super::*;
+
+use rand::Rng;
+
+// Most items a monster can carry at once.
+pub const MONSTER_INVENTORY_SIZE: usize = 3;
+
+// Monsters drink a health potion once their health drops below this fraction.
+const POTION_HEALTH: f32 = 0.5;
+
+// Percentage chance each turn of a monster reading a scroll it holds, while the player is in range.
+const SCROLL_CHANCE: i32 = 30;
+
+impl Object {
+    // Picks up any items lying where a monster is standing, as long as it has room for them.
+    pub fn monster_pick_up(
+        monster_id: usize,
+        tcod: &Tcod,
+        game: &mut Game,
+        characters: &mut [Character],
+        items: &mut HashMap<i32, Object>
+    ) {
+        let (x, y) = characters[monster_id].object.pos();
+
+        // Traps, and anything else which isn't an item, are left where they are.
+        let item_ids: Vec<i32> = items
+            .iter()
+            .filter(|(_, item)| item.item.is_some() && item.pos() == (x, y))
+            .map(|(id, _)| *id)
+            .collect();
+
+        let monster = &mut characters[monster_id];
+        for item_id in item_ids {
+            let inventory = match monster.inventory.as_mut() {
+                Some(inventory) if inventory.len() < MONSTER_INVENTORY_SIZE => inventory,
+                _ => return,
+            };
+            if let Some(item) = items.remove(&item_id) {
+                if tcod.fov.is_in_fov(x, y) {
+                    game.messages.add(
+                        format!("The {} picks up a {}.", monster.object.name, game.identity.name_of(&item)),
+                        item.color,
+                    );
+                }
+                inventory.push(item);
+            }
+        }
+    }
+
+    // Lets a smarter monster use an item from its inventory, in place of attacking.
+    // Items have the same effect as when the player uses them, with the player as the target.
+    // Returns true if the monster used an item.
+    pub fn monster_use_item(monster_id: usize, game: &mut Game, characters: &mut [Character], player: &mut Object) -> bool {
+        let fighter = match characters[monster_id].object.fighter {
+            Some(fighter) if fighter.uses_items => fighter,
+            _ => return false,
+        };
+        let inventory = match characters[monster_id].inventory.as_ref() {
+            Some(inventory) => inventory,
+            None => return false,
+        };
+
+        let distance = characters[monster_id].object.distance_to(player);
+        let wounded = (fighter.hp as f32) < fighter.max_hp as f32 * POTION_HEALTH;
+        let mut rng = rand::thread_rng();
+
+        // Healing comes first, otherwise the monster might read a scroll at the player.
+        let potion = inventory.iter().position(|item| match &item.item {
+            Some(item) => wounded && item.targeting == Targeting::User && item.heals(),
+            None => false,
+        });
+        let choice = potion.or_else(|| inventory.iter().position(|item| {
+            let item = match &item.item {
+                Some(item) => item,
+                None => return false,
+            };
+            // Statuses the player is already under would be wasted.
+            let wasted = item.effects.iter().all(|effect| match *effect {
+                ItemEffect::Status { kind, .. } => player.has_effect(kind),
+                ItemEffect::Damage { .. } => false,
+                _ => true,
+            });
+            let useful = match item.targeting {
+                Targeting::User => false,
+                Targeting::Closest { range } | Targeting::Tile { range } => distance <= range as f32,
+                // Monsters keep out of their own blasts.
+                Targeting::Area { range, radius } => range.map_or(true, |range| distance <= range as f32) && distance > radius,
+            };
+            useful && !wasted && rng.gen_range(0, 100) < SCROLL_CHANCE
+        }));
+
+        let item = match (choice, characters[monster_id].inventory.as_mut()) {
+            (Some(choice), Some(inventory)) => inventory.remove(choice),
+            _ => return false,
+        };
+        let effect = match item.item.clone() {
+            Some(effect) => effect,
+            None => return false,
+        };
+
+        game.messages.add(
+            format!(
+                "The {} {} a {}!",
+                characters[monster_id].object.name, effect.category.verb(), game.identity.name_of(&item)
+            ),
+            item.color,
+        );
+        let targets = match effect.targeting {
+            Targeting::User => vec![Target::Monster(monster_id)],
+            Targeting::Closest { .. } | Targeting::Tile { .. } => vec![Target::Player],
+            Targeting::Area { radius, .. } => Object::targets_around(player.x, player.y, radius, characters, player),
+        };
+        Object::apply_item(&effect, item.color, Target::Monster(monster_id), &targets, game, characters, player, None);
+        true
+    }
+}
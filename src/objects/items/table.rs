@@ -0,0 +1,146 @@
+use super::*;
+use super::equipment::{ Equipment, roll_element };
+use super::identify::Identification;
+use crate::graphics::gui::Messages;
+use crate::environment::spawner::{ Transition, from_dungeon_level };
+
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+
+use rand::distributions::Weighted;
+
+// The item table is read from this file at the start of each game, so it can be tweaked without recompiling.
+const ITEM_FILE: &str = "items.json";
+
+// A copy of the table is built into the game, in case the file is missing or broken.
+const DEFAULT_ITEMS: &str = include_str!("../../../items.json");
+
+// The item grown by nature monsters when they die.
+pub const HERB: &str = "Healing herb";
+
+// Items which the game makes by name, rather than picking from the table, so the table must always have them.
+const REQUIRED_ITEMS: [&str; 1] = [HERB];
+
+// Everything needed to make an item, alongside how likely it is to appear at each depth.
+// Items with no weights are never found lying around, and only appear through other means.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemDef {
+    pub name: String,
+    pub glyph: char,
+    pub color: Color,
+    #[serde(flatten)]
+    pub item: Item,
+    #[serde(default)]
+    pub equipment: Option<Equipment>,
+    #[serde(default)]
+    pub weights: Vec<Transition>,
+}
+
+// Item table definition.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemTable {
+    pub items: Vec<ItemDef>,
+}
+
+impl ItemTable {
+    // Loads the item table from the item file.
+    // If it can't be read, the player is told why, and the built in table is used instead.
+    pub fn load(messages: &mut Messages) -> ItemTable {
+        match ItemTable::from_file() {
+            Ok(table) => table,
+            Err(e) => {
+                messages.add(format!("Could not load {}, using the default items: {}", ITEM_FILE, e), ORANGE);
+                ItemTable::built_in()
+            },
+        }
+    }
+
+    // The item table built into the game.
+    pub fn built_in() -> ItemTable {
+        serde_json::from_str(DEFAULT_ITEMS).expect("Default item table is invalid")
+    }
+
+    fn from_file() -> Result<ItemTable, Box<dyn Error>> {
+        let mut json_items = String::new();
+        let mut file = File::open(ITEM_FILE)?;
+        file.read_to_string(&mut json_items)?;
+        let table = serde_json::from_str::<ItemTable>(&json_items)?;
+        table.validate()?;
+        Ok(table)
+    }
+
+    // Checks that every item the game makes by name is in the table.
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for name in REQUIRED_ITEMS.iter() {
+            if self.find(name).is_none() {
+                return Err(format!("the table has no \"{}\"", name).into());
+            }
+        }
+        Ok(())
+    }
+
+    // Finds an item by name.
+    pub fn find(&self, name: &str) -> Option<&ItemDef> {
+        self.items.iter().find(|def| def.name == name)
+    }
+
+    // Weights of every item in the table at a depth, pointing back at their place in the table.
+    pub fn weights(&self, level: u32) -> Vec<Weighted<usize>> {
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(index, def)| Weighted {
+                weight: from_dungeon_level(&def.weights, level),
+                item: index,
+            })
+            .collect()
+    }
+}
+
+impl ItemDef {
+    // Creates the item object described by this definition, disguised as it looks during this run.
+    // Equipment may be given a random element, which is more likely on deeper levels.
+    pub fn create(&self, x: i32, y: i32, level: u32, identity: &Identification) -> Object {
+        let mut name = self.name.clone();
+        let mut equipment = self.equipment;
+        if let Some(base) = equipment {
+            let (elemental, elemental_name) = roll_element(base, &name, level);
+            equipment = Some(elemental);
+            name = elemental_name;
+        }
+
+        let mut new_item = Object::new_item(x, y, self.glyph, &name, self.color, false);
+        new_item.item = Some(self.item.clone());
+        new_item.equipment = equipment;
+        identity.disguise(&mut new_item);
+        new_item
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_table_is_valid() {
+        assert!(ItemTable::built_in().validate().is_ok());
+    }
+
+    #[test]
+    fn missing_herb_is_invalid() {
+        let mut table = ItemTable::built_in();
+        table.items.retain(|def| def.name != HERB);
+        assert!(table.validate().is_err());
+    }
+
+    #[test]
+    fn weights_point_back_at_their_items() {
+        let table = ItemTable::built_in();
+        let weights = table.weights(1);
+        assert_eq!(weights.len(), table.items.len());
+        for (index, weighted) in weights.iter().enumerate() {
+            assert_eq!(weighted.item, index);
+        }
+    }
+}
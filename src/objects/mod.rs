@@ -7,6 +7,16 @@ use npc::ai::*;
 
 pub mod items;
 use items::*;
+use items::equipment::Equipment;
+
+pub mod traps;
+use traps::*;
+
+pub mod effects;
+
+pub mod powers;
+
+pub mod combat;
 
 use serde::{ Serialize, Deserialize };
 
@@ -27,6 +37,8 @@ pub struct Object {
     pub fighter: Option<Fighter>,
     pub ai: Option<Ai>,
     pub item: Option<Item>,
+    pub trap: Option<Trap>,
+    pub equipment: Option<Equipment>,
     pub level: i32,
     pub always_visible: bool,
 }
@@ -76,8 +88,9 @@ impl Object {
     }
 
     // Function to allow fighter-enabled objects to take damage
-    fn take_damage(&mut self, damage: i32, game: &mut Game) -> Option<i32> {
-        // Apply damage if possible.
+    // The damage has already had resistances and weaknesses applied, so it is taken as it is.
+    fn take_damage(&mut self, damage: i32, kind: DamageType, game: &mut Game) -> Option<i32> {
+        self.report_resistance(damage, kind, game);
         if let Some(fighter) = self.fighter.as_mut() {
             if damage > 0 {
                 fighter.hp -= damage;
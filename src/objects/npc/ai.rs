@@ -1,23 +1,68 @@
-use crate::Tcod;
-use crate::environment::{ Game, Map };
-use super::{ Object, super::Character };
+use crate::{ Tcod, COMBAT_NOISE };
+use crate::environment::{ Game, Map, MapTheme, MAP_WIDTH, MAP_HEIGHT, BLIND_RADIUS };
+use super::{ Object, Ability, DamageType, super::Character };
+use crate::objects::effects::EffectKind;
+use crate::objects::combat::{ Attack, resolve };
+use super::enemies::{ generate_family_monster, monster_level_up };
+use crate::pathing::{ Diagonals, walk_cost, grid_index };
+use crate::pathing::astar::astar;
+use crate::pathing::dijkstra::DijkstraMap;
 
 use rand::Rng;
 
+use tcod::line::Line;
+
 use serde::{ Serialize, Deserialize };
 
 use tcod::colors::*;
 
+// Extra cost of walking through a tile which another character is standing on.
+// Monsters will walk around each other where they can, but will queue up in corridors rather than give up.
+const CROWD_COST: i32 = 5;
+
+// How many turns a monster will follow the player's trail, after losing sight of them.
+const TRAIL_LENGTH: i32 = 15;
+
+// Sleeping monsters wake up if the player comes this close.
+const WAKE_DISTANCE: f32 = 3.0;
+
+// Monsters run away once their health drops below this fraction, for a number of turns.
+const FLEE_HEALTH: f32 = 0.25;
+const FLEE_TURNS: i32 = 8;
+
+// Monsters with a ranged attack try to stay at least this far away from the player.
+const KEEP_DISTANCE: f32 = 3.0;
+
+// What a monster does before it has spotted the player.
+// Guards, and anything told to stay still, keep the basic AI and wait where they are.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Idle {
+    Still,
+    Sleep,
+    Wander,
+    Patrol,
+    Guard,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Ai {
     Basic,
-    Confused {
-        previous_ai: Box<Ai>,
-        num_turns: i32,
+    Hunting {
+        target: (i32, i32),
+        turns: i32,
     },
-    Fear {
-        previous_ai: Box<Ai>,
-        num_turns: i32,
+    Wandering {
+        destination: Option<(i32, i32)>,
+    },
+    Sleeping,
+    Patrol {
+        route: Vec<(i32, i32)>,
+        next: usize,
+    },
+    Boss {
+        phase: i32,
+        family: MapTheme,
+        level: u32,
     },
 }
 
@@ -36,6 +81,46 @@ impl Object {
         Object::move_by(id, dx, dy, map, characters);
     }
 
+    // Moves object one step along the shortest path towards a target.
+    // If there is no path to the target, it moves in a straight line instead.
+    fn path_towards(id: usize, target_x: i32, target_y: i32, map: &Map, characters: &mut [Character]) {
+        let start = characters[id].object.pos();
+
+        // Marks every tile which is occupied by a character.
+        let mut occupied = vec![false; (MAP_WIDTH * MAP_HEIGHT) as usize];
+        for character in characters.iter().filter(|character| character.object.blocks) {
+            occupied[grid_index(character.object.x, character.object.y)] = true;
+        }
+
+        let path = astar(start, (target_x, target_y), Diagonals::Always, |x, y| {
+            walk_cost(map, x, y).map(|cost| if occupied[grid_index(x, y)] { cost + CROWD_COST } else { cost })
+        });
+
+        match path.and_then(|path| path.first().cloned()) {
+            Some((x, y)) => Object::move_by(id, x - start.0, y - start.1, map, characters),
+            None => Object::move_towards(id, target_x, target_y, map, characters),
+        }
+    }
+
+    // Checks whether this character can see a target, using its own sight radius.
+    // Any tile which can't be seen through blocks the line of sight.
+    // Blinded characters can only see right next to themselves, and invisible targets can only be seen up close.
+    pub fn can_see(&self, target: &Object, map: &Map) -> bool {
+        // Half a tile is added on when blinded, so that diagonal neighbours can still be seen.
+        let sight = if self.has_effect(EffectKind::Blindness) || target.has_effect(EffectKind::Invisibility) {
+            BLIND_RADIUS as f32 + 0.5
+        } else {
+            self.fighter.map_or(0, |f| f.sight) as f32
+        };
+        if self.distance_to(target) > sight {
+            return false;
+        }
+
+        let target_pos = target.pos();
+        Line::new(self.pos(), target_pos)
+            .all(|(x, y)| (x, y) == target_pos || !map[x as usize][y as usize].block_sight)
+    }
+
     // Calculates distance between object, and another object.
     pub fn distance_to(&self, other: &Object) -> f32 {
         let dx = other.x - self.x;
@@ -46,11 +131,37 @@ impl Object {
     // Depending on the current AI status of the object, activates the relevant AI function.
     pub fn ai_take_turn(monster_id: usize, tcod: &Tcod, game: &mut Game, characters: &mut Vec<Character>, player: &mut Object) {
         use Ai::*;
+
+        // Ranged attacks recharge a little each turn.
+        if let Some(ranged) = characters[monster_id].object.fighter.as_mut().and_then(|f| f.ranged.as_mut()) {
+            ranged.recharge = (ranged.recharge - 1).max(0);
+        }
+
+        // Regenerating monsters heal a little each turn.
+        if let Some(fighter) = characters[monster_id].object.fighter.as_mut() {
+            if let Some(Ability::Regenerate { amount }) = fighter.ability {
+                fighter.hp = (fighter.hp + amount).min(fighter.max_hp);
+            }
+        }
+
+        // Confusion and fear take over from the monster's own AI, which picks up again once they wear off.
+        if characters[monster_id].object.has_effect(EffectKind::Confusion) {
+            Object::stumble(monster_id, game, characters);
+            return;
+        }
+        if characters[monster_id].object.has_effect(EffectKind::Fear) {
+            Object::cower(monster_id, game, characters, player);
+            return;
+        }
+
         if let Some(ai) = characters[monster_id].object.ai.take() {
             let new_ai = match ai {
                 Basic => Object::ai_basic(monster_id, tcod, game, characters, player),
-                Confused{previous_ai, num_turns} => Object::ai_confused(monster_id, tcod, game, characters, previous_ai, num_turns),
-                Fear{previous_ai, num_turns} => Object::ai_fear(monster_id, tcod, game, characters, previous_ai, num_turns),
+                Hunting{target, turns} => Object::ai_hunting(monster_id, tcod, game, characters, player, target, turns),
+                Wandering{destination} => Object::ai_wandering(monster_id, tcod, game, characters, player, destination),
+                Sleeping => Object::ai_sleeping(monster_id, tcod, game, characters, player),
+                Patrol{route, next} => Object::ai_patrol(monster_id, tcod, game, characters, player, route, next),
+                Boss{phase, family, level} => Object::ai_boss(monster_id, tcod, game, characters, player, phase, family, level),
             };
             characters[monster_id].object.ai = Some(new_ai);
         }
@@ -58,110 +169,435 @@ impl Object {
 
     // Because the AI state can change, the different AI types return an AI to insert into the object.
     fn ai_basic(monster_id: usize, tcod: &Tcod, game: &mut Game, characters: &mut [Character], player: &mut Object) -> Ai {
-        // A basic monster taking its turn normally.
-        // If you can see it, it can see you too.
-        let (monster_x, monster_y) = characters[monster_id].object.pos();
-        if tcod.fov.is_in_fov(monster_x, monster_y) {
-            if characters[monster_id].object.distance_to(player) >= 2.0 {
-                // Moves towards player if far away.
-                let (player_x, player_y) = player.pos();
-                Object::move_towards(monster_id, player_x, player_y, &game.map, characters);
-            } else if player.fighter.map_or(false, |f| f.hp > 0) {
-                // Close enough to attack.
-                characters[monster_id].object.monster_attack(game, player);
+        // A basic monster stays put until it spots the player, and then starts hunting them down.
+        if !characters[monster_id].object.can_see(player, &game.map) {
+            return Ai::Basic;
+        }
+
+        let hunting = Ai::Hunting {
+            target: player.pos(),
+            turns: TRAIL_LENGTH,
+        };
+
+        // Badly wounded monsters run away, and pick up the hunt again once their fear wears off.
+        // They only run once for each wound, so must heal up and be wounded again before fleeing again.
+        let monster = &mut characters[monster_id].object;
+        let wounded = monster.fighter.map_or(false, |f| (f.hp as f32) < f.max_hp as f32 * FLEE_HEALTH);
+        let should_flee = match monster.fighter.as_mut() {
+            Some(fighter) => {
+                let should_flee = wounded && !fighter.fled;
+                fighter.fled = wounded;
+                should_flee
+            },
+            None => false,
+        };
+        if should_flee {
+            if tcod.fov.is_in_fov(monster.x, monster.y) {
+                game.messages.add(format!("The {} turns to flee!", monster.name), LIGHT_GREY);
+            }
+            monster.add_effect(EffectKind::Fear, FLEE_TURNS, 0, game);
+            Object::cower(monster_id, game, characters, player);
+            return hunting;
+        }
+
+        Object::chase(monster_id, game, characters, player);
+        hunting
+    }
+
+    // Moves towards the player, or attacks them if they're close enough.
+    // Monsters with a ranged attack shoot whenever they have a clear shot, and back off while reloading.
+    fn chase(monster_id: usize, game: &mut Game, characters: &mut [Character], player: &mut Object) {
+        // Smarter monsters may drink a potion, or read a scroll, instead.
+        if Object::monster_use_item(monster_id, game, characters, player) {
+            return;
+        }
+
+        let distance = characters[monster_id].object.distance_to(player);
+        let ranged = characters[monster_id].object.fighter.and_then(|f| f.ranged);
+
+        if let Some(ranged) = ranged {
+            let in_range = distance <= ranged.range as f32;
+            let (monster_x, monster_y) = characters[monster_id].object.pos();
+            let clear_shot = in_range && Object::line_of_fire((monster_x, monster_y), player.pos(), &game.map, characters);
+
+            if ranged.ready() && clear_shot && player.fighter.map_or(false, |f| f.hp > 0) {
+                let damage = characters[monster_id].object.ranged_attack(game, player);
+                Object::ability_on_hit(monster_id, damage, game, characters, player);
+                return;
+            }
+
+            // Keeps its distance, as long as it still has something to shoot with.
+            let has_ammo = ranged.ammo.map_or(true, |ammo| ammo > 0);
+            if has_ammo && distance < KEEP_DISTANCE && Object::flee_from(monster_id, player.pos(), &game.map, characters) {
+                return;
             }
         }
-        Ai::Basic
+
+        if distance >= 2.0 {
+            // Moves towards player if far away.
+            let (player_x, player_y) = player.pos();
+            Object::path_towards(monster_id, player_x, player_y, &game.map, characters);
+        } else if player.fighter.map_or(false, |f| f.hp > 0) {
+            // Close enough to attack.
+            let damage = characters[monster_id].object.monster_attack(game, player);
+            Object::ability_on_hit(monster_id, damage, game, characters, player);
+        }
     }
 
-    // Returns AI confused, until the confusion wears off, then it returns its previous AI.
-    fn ai_confused(
+    // Chases the player while they can be seen.
+    // Once they're out of sight, the monster heads to where it last saw them, until the trail goes cold.
+    fn ai_hunting(
         monster_id: usize,
-        _tcod: &Tcod,
+        tcod: &Tcod,
         game: &mut Game,
         characters: &mut [Character],
-        previous_ai: Box<Ai>,
-        num_turns: i32
+        player: &mut Object,
+        target: (i32, i32),
+        turns: i32,
     ) -> Ai {
-        if num_turns >= 0 {
-            // Still confused ...
-            // Move in a random direction, and decrease the number of turns confused.
-            Object::move_by(
-                monster_id,
-                rand::thread_rng().gen_range(-1, 2),
-                rand::thread_rng().gen_range(-1, 2),
-                &game.map,
-                characters,
-            );
-            Ai::Confused {
-                previous_ai: previous_ai,
-                num_turns: num_turns - 1,
+        if characters[monster_id].object.can_see(player, &game.map) {
+            return Object::ai_basic(monster_id, tcod, game, characters, player);
+        }
+
+        if turns > 0 && characters[monster_id].object.pos() != target {
+            Object::path_towards(monster_id, target.0, target.1, &game.map, characters);
+            Ai::Hunting {
+                target: target,
+                turns: turns - 1,
             }
         } else {
-            // Restore the previous AI, and delete this one.
-            game.messages.add(
-                format!("The {} is no longer confused!", characters[monster_id].object.name),
-                RED,
-            );
-            *previous_ai
+            Ai::Wandering { destination: None }
         }
     }
 
-    // Returns AI confused, until the confusion wears off, then it returns its previous AI.
-    fn ai_fear(
+    // Wanders between random reachable tiles, until the player is spotted.
+    fn ai_wandering(
         monster_id: usize,
-        _tcod: &Tcod,
+        tcod: &Tcod,
         game: &mut Game,
         characters: &mut [Character],
-        previous_ai: Box<Ai>,
-        num_turns: i32,
+        player: &mut Object,
+        destination: Option<(i32, i32)>,
     ) -> Ai {
-        if num_turns >= 0 {
-            // Still scared ...
-            // Stay frozen
-            Ai::Fear {
-                previous_ai: previous_ai,
-                num_turns: num_turns - 1,
-            }
+        if characters[monster_id].object.can_see(player, &game.map) {
+            return Object::ai_basic(monster_id, tcod, game, characters, player);
+        }
+
+        // A new destination is picked once the old one has been reached.
+        let position = characters[monster_id].object.pos();
+        let destination = match destination {
+            Some(destination) if destination != position => destination,
+            _ => match Object::random_destination(game) {
+                Some(destination) => destination,
+                None => return Ai::Wandering { destination: None },
+            },
+        };
+
+        // If the monster couldn't move, the destination is given up on.
+        Object::path_towards(monster_id, destination.0, destination.1, &game.map, characters);
+        if characters[monster_id].object.pos() == position {
+            Ai::Wandering { destination: None }
+        } else {
+            Ai::Wandering { destination: Some(destination) }
+        }
+    }
+
+    // Sleeps until the player comes close, or a noise is made nearby.
+    // Monsters woken by a noise go to investigate where it came from.
+    fn ai_sleeping(monster_id: usize, tcod: &Tcod, game: &mut Game, characters: &mut [Character], player: &mut Object) -> Ai {
+        let monster = &characters[monster_id].object;
+        let disturbed = monster.distance_to(player) < WAKE_DISTANCE && monster.can_see(player, &game.map);
+        let heard = game.noises
+            .iter()
+            .find(|&&(x, y, radius)| monster.distance(x, y) <= radius as f32)
+            .map(|&(x, y, _)| (x, y));
+
+        if !disturbed && heard.is_none() {
+            return Ai::Sleeping;
+        }
+
+        if tcod.fov.is_in_fov(monster.x, monster.y) {
+            game.messages.add(format!("The {} wakes up!", monster.name), LIGHT_GREY);
+        }
+
+        match heard {
+            Some(target) if !disturbed => Ai::Hunting {
+                target: target,
+                turns: TRAIL_LENGTH,
+            },
+            _ => Object::ai_basic(monster_id, tcod, game, characters, player),
+        }
+    }
+
+    // Walks a loop between the stops of a route, until the player is spotted.
+    fn ai_patrol(
+        monster_id: usize,
+        tcod: &Tcod,
+        game: &mut Game,
+        characters: &mut [Character],
+        player: &mut Object,
+        route: Vec<(i32, i32)>,
+        next: usize,
+    ) -> Ai {
+        if characters[monster_id].object.can_see(player, &game.map) {
+            return Object::ai_basic(monster_id, tcod, game, characters, player);
+        }
+        if route.is_empty() {
+            return Ai::Wandering { destination: None };
+        }
+
+        // A stop counts as reached once the monster is next to it, in case something is standing on it.
+        let (stop_x, stop_y) = route[next % route.len()];
+        let next = if characters[monster_id].object.distance(stop_x, stop_y) < 2.0 {
+            (next + 1) % route.len()
         } else {
-            // Restore the previous AI, and delete this one.
+            next % route.len()
+        };
+
+        let (stop_x, stop_y) = route[next];
+        Object::path_towards(monster_id, stop_x, stop_y, &game.map, characters);
+        Ai::Patrol {
+            route: route,
+            next: next,
+        }
+    }
+
+    // Picks a random open tile for a wandering monster to head towards.
+    // Tiles which don't belong to a room or corridor are never picked.
+    fn random_destination(game: &Game) -> Option<(i32, i32)> {
+        let max_tries = 20;
+        for _ in 0..max_tries {
+            let x = rand::thread_rng().gen_range(1, MAP_WIDTH - 1);
+            let y = rand::thread_rng().gen_range(1, MAP_HEIGHT - 1);
+            if game.layout.region_at(x, y).is_some() && !game.map[x as usize][y as usize].blocked {
+                return Some((x, y));
+            }
+        }
+        None
+    }
+
+    // Confused monsters stumble about in a random direction.
+    fn stumble(monster_id: usize, game: &mut Game, characters: &mut [Character]) {
+        Object::move_by(
+            monster_id,
+            rand::thread_rng().gen_range(-1, 2),
+            rand::thread_rng().gen_range(-1, 2),
+            &game.map,
+            characters,
+        );
+    }
+
+    // Frightened monsters run away from the player.
+    // A monster with nowhere left to run will turn and fight.
+    fn cower(monster_id: usize, game: &mut Game, characters: &mut [Character], player: &mut Object) {
+        let fled = Object::flee_from(monster_id, player.pos(), &game.map, characters);
+        let cornered = !fled && characters[monster_id].object.distance_to(player) < 2.0;
+        if cornered && player.fighter.map_or(false, |f| f.hp > 0) {
             game.messages.add(
-                format!("The {} is no longer scared!", characters[monster_id].object.name),
+                format!("The {} is cornered, and lashes out!", characters[monster_id].object.name),
                 RED,
             );
-            *previous_ai
+            let damage = characters[monster_id].object.monster_attack(game, player);
+            Object::ability_on_hit(monster_id, damage, game, characters, player);
         }
     }
 
-    // Just a simple attack on another object
-    fn monster_attack(&self, game: &mut Game, mut other: &mut Object) {
-        let mut rng = rand::thread_rng();
-        let attack = (self.fighter.map_or(1, |f| f.power)) as f32 + rng.gen_range(-1.0, 1.0);
-        let defense = (other.fighter.map_or(1, |f| f.defense)) as f32 + rng.gen_range(-1.0, 1.0);
-        let mut level_mod = ((self.level - other.level) / 3) as f32;
-        if level_mod <= 0.0 { level_mod = 1.0; }
+    // Takes a step away from a target, following a flight map.
+    // Returns false if there was nowhere further away to go.
+    fn flee_from(monster_id: usize, target: (i32, i32), map: &Map, characters: &mut [Character]) -> bool {
+        let flight = DijkstraMap::flight(&[target], Diagonals::Always, |x, y| walk_cost(map, x, y));
 
-        let damage = ((attack * level_mod) - defense).round() as i32;
-        if damage > 0 {
-            // Target takes damage.
-            game.messages.add(
-                format!(
-                    "{} attacks {} dealing {} damage.",
-                    self.name, other.name, damage
-                ),
-                self.color,
-            );
-            Object::player_damage(damage, game, &mut other);
+        let (x, y) = characters[monster_id].object.pos();
+        let step = flight.downhill(x, y, |next_x, next_y| {
+            if (next_x, next_y) == target || Object::is_blocked(next_x, next_y, map, characters) {
+                None
+            } else {
+                walk_cost(map, next_x, next_y)
+            }
+        });
+
+        match step {
+            Some((step_x, step_y)) => {
+                Object::move_by(monster_id, step_x - x, step_y - y, map, characters);
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Bosses fight like basic monsters, but move through scripted phases as they are worn down.
+    fn ai_boss(
+        monster_id: usize,
+        _tcod: &Tcod,
+        game: &mut Game,
+        characters: &mut Vec<Character>,
+        player: &mut Object,
+        phase: i32,
+        family: MapTheme,
+        level: u32,
+    ) -> Ai {
+        // Works out which phase the boss should be in, based on its remaining health.
+        let health = characters[monster_id].object.fighter.map_or(1.0, |f| f.hp as f32 / f.max_hp as f32);
+        let new_phase = if health <= 0.33 {
+            3
+        } else if health <= 0.66 {
+            2
         } else {
+            1
+        };
+
+        // Each phase only begins once, even if the boss were to heal.
+        if new_phase > phase {
+            let name = characters[monster_id].object.name.clone();
+            if new_phase >= 2 && phase < 2 {
+                // Second phase: The boss becomes enraged, and hits harder.
+                game.messages.add(format!("{} flies into a rage!", name), LIGHT_RED);
+                characters[monster_id].object.fighter.as_mut().map(|f| f.power += 2 + level as i32 / 5);
+            }
+            if new_phase >= 3 {
+                // Final phase: The boss calls for help.
+                game.messages.add(format!("{} calls out, and its servants answer!", name), LIGHT_RED);
+                Object::summon_servants(monster_id, game, characters, player, family, level);
+            }
+        }
+
+        // Otherwise, the boss fights just like any other monster, but never runs away.
+        if characters[monster_id].object.can_see(player, &game.map) {
+            Object::chase(monster_id, game, characters, player);
+        }
+        Ai::Boss {
+            phase: new_phase.max(phase),
+            family: family,
+            level: level,
+        }
+    }
+
+    // Places servants of the same family as a boss into the free tiles surrounding it.
+    fn summon_servants(monster_id: usize, game: &Game, characters: &mut Vec<Character>, player: &Object, family: MapTheme, level: u32) {
+        let (boss_x, boss_y) = characters[monster_id].object.pos();
+        let mut summoned = 0;
+        let max_servants = 2;
+
+        for dx in -1..2 {
+            for dy in -1..2 {
+                let (x, y) = (boss_x + dx, boss_y + dy);
+                if summoned < max_servants &&
+                !Object::is_blocked(x, y, &game.map, characters) &&
+                player.pos() != (x, y) {
+                    let mut servant = generate_family_monster(x, y, 2, level, family, &game.item_table, &game.identity);
+                    servant.object.alive = true;
+
+                    // Level up the monster to increase the difficulty.
+                    let mut level_up = level - 1;
+                    while level_up > 0 {
+                        servant.object.fighter.as_mut().map(|f| monster_level_up(f));
+                        level_up -= 1;
+                    }
+
+                    characters.push(servant);
+                    summoned += 1;
+                }
+            }
+        }
+    }
+
+    // Checks that nothing stands in the way of a shot between two points.
+    // Walls and other characters both block the shot.
+    pub fn line_of_fire(from: (i32, i32), to: (i32, i32), map: &Map, characters: &[Character]) -> bool {
+        Line::new(from, to).all(|(x, y)| {
+            (x, y) == to || (!map[x as usize][y as usize].blocked &&
+            !characters.iter().any(|c| c.object.blocks && c.object.pos() == (x, y)))
+        })
+    }
+
+    // Shoots at another object, using up ammo and starting the cooldown.
+    // Returns the damage dealt.
+    fn ranged_attack(&mut self, game: &mut Game, mut other: &mut Object) -> i32 {
+        let ranged = match self.fighter.as_mut().and_then(|f| f.ranged.as_mut()) {
+            Some(ranged) => {
+                ranged.recharge = ranged.cooldown;
+                ranged.ammo = ranged.ammo.map(|ammo| ammo - 1);
+                *ranged
+            },
+            None => return 0,
+        };
+
+        // The player's powers can turn the shot aside.
+        if other.is_player() && Object::deflects_projectile(game) {
             game.messages.add(
-                format!(
-                    "{} attacks {} but it has no effect!",
-                    self.name, other.name
-                ),
-                WHITE,
+                format!("{} {} {}, but the shot is turned aside!", self.name, ranged.projectile.description(), other.name),
+                LIGHT_CYAN,
             );
+            return 0;
+        }
+
+        let result = resolve(&Attack::ranged(self, &ranged), other);
+        game.make_noise(self.x, self.y, COMBAT_NOISE);
+        game.stats.record_received(&result);
+        game.messages.add(
+            result.describe(&self.name, ranged.projectile.description(), &other.name),
+            result.color(self.color),
+        );
+        if result.landed() {
+            Object::player_damage(result.damage, result.kind, game, &mut other);
         }
+        result.damage
     }
 
+    // Just a simple attack on another object.
+    // Returns the damage dealt.
+    fn monster_attack(&self, game: &mut Game, mut other: &mut Object) -> i32 {
+        let result = resolve(&Attack::melee(self, DamageType::Physical), other);
+        game.make_noise(self.x, self.y, COMBAT_NOISE);
+        game.stats.record_received(&result);
+        game.messages.add(result.describe(&self.name, "attacks", &other.name), result.color(self.color));
+        if result.landed() {
+            Object::player_damage(result.damage, result.kind, game, &mut other);
+        }
+        result.damage
+    }
+
+    // Applies the on-hit ability of a monster, once it has damaged the player.
+    fn ability_on_hit(monster_id: usize, damage: i32, game: &mut Game, characters: &mut [Character], player: &mut Object) {
+        let ability = characters[monster_id].object.fighter.and_then(|f| f.ability);
+        if damage <= 0 || player.fighter.map_or(true, |f| f.hp <= 0) {
+            return;
+        }
 
+        match ability {
+            Some(Ability::Ignite { damage: burn_damage, turns }) => {
+                player.add_effect(EffectKind::Burning, turns, burn_damage, game);
+            },
+            Some(Ability::Slow { turns }) => player.add_effect(EffectKind::Slow, turns, 0, game),
+            Some(Ability::Blind { turns }) => player.add_effect(EffectKind::Blindness, turns, 0, game),
+            Some(Ability::Drain { percent }) => {
+                let drained = (damage * percent / 100).max(1);
+                if let Some(fighter) = characters[monster_id].object.fighter.as_mut() {
+                    fighter.hp = (fighter.hp + drained).min(fighter.max_hp);
+                }
+                game.messages.add(
+                    format!("{} drains {} life from {}.", characters[monster_id].object.name, drained, player.name),
+                    DARK_PURPLE,
+                );
+            },
+
+            // The player is pushed directly away from the monster, until they hit something.
+            Some(Ability::Knockback { distance }) => {
+                let (monster_x, monster_y) = characters[monster_id].object.pos();
+                let (dx, dy) = ((player.x - monster_x).signum(), (player.y - monster_y).signum());
+                let mut pushed = 0;
+                while pushed < distance && !Object::is_blocked(player.x + dx, player.y + dy, &game.map, characters) {
+                    player.x += dx;
+                    player.y += dy;
+                    pushed += 1;
+                }
+                if pushed > 0 {
+                    game.messages.add(format!("{} is knocked back!", player.name), DARK_AMBER);
+                }
+            },
+
+            // Reflection and regeneration don't do anything on a hit.
+            Some(Ability::Reflect { .. }) | Some(Ability::Regenerate { .. }) | None => {},
+        }
+    }
 }
@@ -0,0 +1,134 @@
+use crate::environment::Game;
+use crate::objects::combat::{ Attack, resolve };
+use crate::objects::items::table::HERB;
+
+use super::{ Object, DamageType, DeathBehavior, Remains, super::Character };
+use super::enemies::{ generate_split_blob, monster_level_up };
+
+use std::collections::HashMap;
+
+use tcod::colors::*;
+
+impl Object {
+    // Carries out the death behaviors of every monster slain this turn, and drops whatever they were carrying.
+    // Anything killed along the way, such as by an explosion, has its own behaviors carried out too.
+    pub fn resolve_deaths(
+        game: &mut Game,
+        characters: &mut Vec<Character>,
+        items: &mut HashMap<i32, Object>,
+        player: &mut Object,
+    ) {
+        while !game.remains.is_empty() {
+            let remains = game.remains.remove(0);
+            match remains.behavior {
+                DeathBehavior::Split { tier } => Object::split(remains, tier, game, characters, player),
+                DeathBehavior::Explode { damage, radius } => Object::explode(remains, damage, radius, game, characters, player),
+                DeathBehavior::Herbs { count } => Object::grow_herbs(remains, count, game, items),
+            }
+        }
+
+        // Any monster with an inventory drops it where it fell.
+        for character in characters.iter_mut() {
+            if character.object.fighter.is_some() {
+                continue;
+            }
+            if let Some(inventory) = character.inventory.take() {
+                for mut item in inventory {
+                    item.set_pos(character.object.x, character.object.y);
+                    game.messages.add(format!("A {} falls to the ground.", game.identity.name_of(&item)), item.color);
+                    items.insert(Object::free_item_id(items), item);
+                }
+            }
+        }
+    }
+
+    // Splits a slain blob into two smaller blobs, in the free tiles around where it fell.
+    fn split(remains: Remains, tier: i32, game: &mut Game, characters: &mut Vec<Character>, player: &Object) {
+        let mut spawned = 0;
+        let max_blobs = 2;
+
+        for dx in -1..2 {
+            for dy in -1..2 {
+                let (x, y) = (remains.x + dx, remains.y + dy);
+                if spawned < max_blobs && !Object::is_blocked(x, y, &game.map, characters) && player.pos() != (x, y) {
+                    let mut blob = generate_split_blob(x, y, tier, remains.family);
+                    blob.object.alive = true;
+
+                    // Split blobs are as strong as any other monster on the floor, but are only worth half as much.
+                    let mut level_up = game.dungeon_level - 1;
+                    while level_up > 0 {
+                        blob.object.fighter.as_mut().map(|f| monster_level_up(f));
+                        level_up -= 1;
+                    }
+                    blob.object.fighter.as_mut().map(|f| f.exp /= 2);
+
+                    characters.push(blob);
+                    spawned += 1;
+                }
+            }
+        }
+
+        if spawned > 0 {
+            game.messages.add("The slain blob splits into smaller blobs!", LIGHTEST_GREEN);
+        }
+    }
+
+    // Blows up a slain monster, burning everything standing around it, the player included.
+    // Monsters killed by the explosion still award experience to the player.
+    fn explode(
+        remains: Remains,
+        damage: i32,
+        radius: f32,
+        game: &mut Game,
+        characters: &mut [Character],
+        player: &mut Object,
+    ) {
+        game.messages.add("The body bursts apart in a ball of flame!", FLAME);
+        let attack = Attack::flat(damage, DamageType::Fire);
+
+        for id in 0..characters.len() {
+            if characters[id].object.fighter.is_some() && characters[id].object.distance(remains.x, remains.y) < radius {
+                let result = resolve(&attack, &characters[id].object);
+                game.messages.add(
+                    format!("{} is caught in the explosion, taking {} damage!", characters[id].object.name, result.damage),
+                    FLAME,
+                );
+                if let Some(exp) = characters[id].object.take_damage(result.damage, result.kind, game) {
+                    player.fighter.as_mut().map(|f| f.exp += exp);
+                }
+            }
+        }
+
+        if player.alive && player.distance(remains.x, remains.y) < radius {
+            let result = resolve(&attack, player);
+            game.stats.record_received(&result);
+            game.messages.add(format!("You are caught in the explosion, taking {} damage!", result.damage), FLAME);
+            Object::player_damage(result.damage, result.kind, game, player);
+        }
+    }
+
+    // Scatters healing herbs over the open ground where a nature monster fell.
+    fn grow_herbs(remains: Remains, count: i32, game: &mut Game, items: &mut HashMap<i32, Object>) {
+        // The item table is checked for herbs when it is loaded.
+        let herb = match game.item_table.find(HERB) {
+            Some(herb) => herb.clone(),
+            None => return,
+        };
+        let mut grown = 0;
+
+        for dx in -1..2 {
+            for dy in -1..2 {
+                let (x, y) = (remains.x + dx, remains.y + dy);
+                let occupied = items.values().any(|item| item.pos() == (x, y));
+                if grown < count && !game.map[x as usize][y as usize].blocked && !occupied {
+                    items.insert(Object::free_item_id(items), herb.create(x, y, game.dungeon_level, &game.identity));
+                    grown += 1;
+                }
+            }
+        }
+
+        if grown > 0 {
+            game.messages.add("Healing herbs sprout from the fallen body.", GREEN);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use super::*;
+
+// Syllables used to build the unique name of each boss.
+const NAME_STARTS: [&str; 8] = ["Mor", "Gul", "Vex", "Thra", "Zan", "Kar", "Ul", "Dra"];
+const NAME_ENDS: [&str; 8] = ["goth", "mir", "rax", "zul", "dun", "thas", "gor", "vyn"];
+
+// Every trait family a boss can draw its second trait from.
+const FAMILIES: [MapTheme; 7] = [
+    MapTheme::Fire,
+    MapTheme::Nature,
+    MapTheme::Water,
+    MapTheme::Light,
+    MapTheme::Death,
+    MapTheme::Crystal,
+    MapTheme::Earth,
+];
+
+// Creates a unique boss monster.
+// A boss is a powerful base monster, carrying the trait of the map theme, alongside a second random trait.
+pub fn generate_boss(x: i32, y: i32, level: u32, theme: MapTheme, table: &ItemTable, identity: &Identification) -> Character {
+    let mut rng = rand::thread_rng();
+
+    // The primary trait always matches the theme of the floor.
+    // The secondary trait comes from any other family, as the same trait applied twice would double up its resistances.
+    let primary_family = theme;
+    let others: Vec<MapTheme> = FAMILIES.iter().cloned().filter(|&family| family != primary_family).collect();
+    let secondary_family = others[rng.gen_range(0, others.len())];
+
+    let mut boss = get_monster(x, y, level, 3);
+    boss = apply_trait(boss, secondary_family, 2);
+    boss = apply_trait(boss, primary_family, 3);
+
+    // Gives the boss its own name.
+    let unique_name = format!(
+        "{}{}",
+        NAME_STARTS[rng.gen_range(0, NAME_STARTS.len())],
+        NAME_ENDS[rng.gen_range(0, NAME_ENDS.len())],
+    );
+    boss.object.name = format!("{}, the {}", unique_name, boss.object.name);
+    boss.object.corpse_type = "'s remains".into();
+
+    // Bosses are far tougher than regular monsters, and worth far more experience.
+    boss.object.fighter.as_mut().map(|f| {
+        f.exp *= 5;
+        f.max_hp *= 3;
+        f.hp = f.max_hp;
+        f.sight += 4;
+        f.accuracy += 10;
+        f.on_death = DeathCallback::Boss;
+    });
+
+    // Bosses are levelled up further than the monsters around them.
+    let mut level_up = level + 3;
+    while level_up > 0 {
+        boss.object.fighter.as_mut().map(|f| monster_level_up(f));
+        level_up -= 1;
+    }
+
+    boss.object.ai = Some(Ai::Boss {
+        phase: 1,
+        family: primary_family,
+        level: level,
+    });
+    boss.object.alive = true;
+    boss.inventory = Some(carried_items(table, identity, level));
+
+    boss
+}
@@ -13,6 +13,18 @@ pub fn blob(x: i32, y: i32, tier: i32) -> Character {
         hp: 30,
         defense: 5,
         power: 5,
+        accuracy: 75,
+        evasion: 0,
+        sight: 4,
+        idle: Idle::Sleep,
+        fled: false,
+        ranged: None,
+        uses_items: false,
+        ability: None,
+        effects: StatusEffects::default(),
+        resistances: Resistances::default(),
+        essence: None,
+        death_behaviors: DeathBehaviors::default(),
         on_death: DeathCallback::Monster,
     };
 
@@ -22,6 +34,18 @@ pub fn blob(x: i32, y: i32, tier: i32) -> Character {
         hp: 45,
         defense: 10,
         power: 10,
+        accuracy: 80,
+        evasion: 0,
+        sight: 5,
+        idle: Idle::Sleep,
+        fled: false,
+        ranged: None,
+        uses_items: false,
+        ability: None,
+        effects: StatusEffects::default(),
+        resistances: Resistances::default(),
+        essence: None,
+        death_behaviors: DeathBehaviors::new(DeathBehavior::Split { tier: 1 }),
         on_death: DeathCallback::Monster,
     };
 
@@ -31,6 +55,18 @@ pub fn blob(x: i32, y: i32, tier: i32) -> Character {
         hp: 65,
         defense: 15,
         power: 15,
+        accuracy: 85,
+        evasion: 5,
+        sight: 6,
+        idle: Idle::Sleep,
+        fled: false,
+        ranged: None,
+        uses_items: false,
+        ability: None,
+        effects: StatusEffects::default(),
+        resistances: Resistances::default(),
+        essence: None,
+        death_behaviors: DeathBehaviors::new(DeathBehavior::Split { tier: 2 }),
         on_death: DeathCallback::Monster,
     };
 
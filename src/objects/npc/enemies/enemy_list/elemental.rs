@@ -13,6 +13,18 @@ pub fn elemental(x: i32, y: i32, tier: i32) -> Character {
         hp: 20,
         defense: 0,
         power: 3,
+        accuracy: 85,
+        evasion: 5,
+        sight: 7,
+        idle: Idle::Patrol,
+        fled: false,
+        ranged: None,
+        uses_items: true,
+        ability: None,
+        effects: StatusEffects::default(),
+        resistances: Resistances::default(),
+        essence: None,
+        death_behaviors: DeathBehaviors::default(),
         on_death: DeathCallback::Monster,
     };
 
@@ -22,6 +34,18 @@ pub fn elemental(x: i32, y: i32, tier: i32) -> Character {
         hp: 25,
         defense: 0,
         power: 10,
+        accuracy: 88,
+        evasion: 10,
+        sight: 8,
+        idle: Idle::Patrol,
+        fled: false,
+        ranged: None,
+        uses_items: true,
+        ability: None,
+        effects: StatusEffects::default(),
+        resistances: Resistances::default(),
+        essence: None,
+        death_behaviors: DeathBehaviors::default(),
         on_death: DeathCallback::Monster,
     };
 
@@ -31,6 +55,18 @@ pub fn elemental(x: i32, y: i32, tier: i32) -> Character {
         hp: 35,
         defense: 4,
         power: 16,
+        accuracy: 90,
+        evasion: 10,
+        sight: 9,
+        idle: Idle::Patrol,
+        fled: false,
+        ranged: None,
+        uses_items: true,
+        ability: None,
+        effects: StatusEffects::default(),
+        resistances: Resistances::default(),
+        essence: None,
+        death_behaviors: DeathBehaviors::default(),
         on_death: DeathCallback::Monster,
     };
 
@@ -13,6 +13,18 @@ pub fn lizard(x: i32, y: i32, tier: i32) -> Character {
         hp: 25,
         defense: 2,
         power: 2,
+        accuracy: 80,
+        evasion: 10,
+        sight: 6,
+        idle: Idle::Wander,
+        fled: false,
+        ranged: None,
+        uses_items: true,
+        ability: None,
+        effects: StatusEffects::default(),
+        resistances: Resistances::default(),
+        essence: None,
+        death_behaviors: DeathBehaviors::default(),
         on_death: DeathCallback::Monster,
     };
 
@@ -22,6 +34,18 @@ pub fn lizard(x: i32, y: i32, tier: i32) -> Character {
         hp: 30,
         defense: 2,
         power: 8,
+        accuracy: 85,
+        evasion: 15,
+        sight: 7,
+        idle: Idle::Wander,
+        fled: false,
+        ranged: Some(RangedAttack::new(Projectile::Spit, 4, 6, 3, None)),
+        uses_items: true,
+        ability: None,
+        effects: StatusEffects::default(),
+        resistances: Resistances::default(),
+        essence: None,
+        death_behaviors: DeathBehaviors::default(),
         on_death: DeathCallback::Monster,
     };
 
@@ -31,6 +55,18 @@ pub fn lizard(x: i32, y: i32, tier: i32) -> Character {
         hp: 45,
         defense: 8,
         power: 12,
+        accuracy: 90,
+        evasion: 20,
+        sight: 8,
+        idle: Idle::Wander,
+        fled: false,
+        ranged: Some(RangedAttack::new(Projectile::Spit, 5, 10, 3, None)),
+        uses_items: true,
+        ability: None,
+        effects: StatusEffects::default(),
+        resistances: Resistances::default(),
+        essence: None,
+        death_behaviors: DeathBehaviors::default(),
         on_death: DeathCallback::Monster,
     };
 
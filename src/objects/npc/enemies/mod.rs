@@ -1,10 +1,14 @@
 pub mod enemy_list;
 pub mod traits;
+pub mod boss;
 
-use traits::get_trait;
+use traits::{ get_trait_family, trait_from_family };
 use enemy_list::get_monster;
 
 use crate::environment::MapTheme;
+use crate::environment::spawner::item_spawns::carried_items;
+use crate::objects::items::table::ItemTable;
+use crate::objects::items::identify::Identification;
 use crate::objects::Character;
 use super::ai::*;
 use super::*;
@@ -27,6 +31,8 @@ impl Object {
             fighter: None,
             ai: Some(Ai::Basic),
             item: None,
+            trap: None,
+            equipment: None,
             level: 1,
             always_visible: false,
             },
@@ -50,11 +56,32 @@ pub fn monster_level_up(mut fighter: &mut Fighter) {
 }
 
 // Creates a new monster by pulling from random characteristics.
-pub fn generate_monster(x: i32, y: i32, tier: i32, level: u32, theme: MapTheme) -> Character {
-
+pub fn generate_monster(x: i32, y: i32, tier: i32, level: u32, theme: MapTheme, table: &ItemTable, identity: &Identification) -> Character {
     // Selects random base monster and trait.
-    let enemy_trait = get_trait(theme, tier);
-    let mut monster = get_monster(x, y, level, tier);
+    let mut monster = apply_trait(get_monster(x, y, level, tier), get_trait_family(theme), tier);
+    monster.inventory = Some(carried_items(table, identity, level));
+    monster
+}
+
+// Creates a new random monster, which is guaranteed to belong to the given trait family.
+pub fn generate_family_monster(x: i32, y: i32, tier: i32, level: u32, family: MapTheme, table: &ItemTable, identity: &Identification) -> Character {
+    let mut monster = apply_trait(get_monster(x, y, level, tier), family, tier);
+    monster.inventory = Some(carried_items(table, identity, level));
+    monster
+}
+
+// Creates one of the smaller blobs a slain blob splits into, carrying the trait family of its parent.
+pub fn generate_split_blob(x: i32, y: i32, tier: i32, family: Option<MapTheme>) -> Character {
+    let blob = enemy_list::blob::blob(x, y, tier);
+    match family {
+        Some(family) => apply_trait(blob, family, tier),
+        None => blob,
+    }
+}
+
+// Merges a trait into a base monster.
+fn apply_trait(mut monster: Character, family: MapTheme, tier: i32) -> Character {
+    let enemy_trait = trait_from_family(family, tier);
 
     // Changes base monster variables to reflect the trait.
     monster.object.name = format!("{}{}", enemy_trait.name, monster.object.name);
@@ -68,6 +95,20 @@ pub fn generate_monster(x: i32, y: i32, tier: i32, level: u32, theme: MapTheme)
         f.hp += enemy_trait.hp;
         f.defense += enemy_trait.defense;
         f.power += enemy_trait.power;
+
+        // A ranged attack from a trait replaces any the base monster already had.
+        if enemy_trait.ranged.is_some() {
+            f.ranged = enemy_trait.ranged;
+        }
+        f.ability = Some(enemy_trait.ability);
+        f.resistances.add(enemy_trait.resists.0, enemy_trait.resists.1);
+        f.resistances.add(enemy_trait.weak_to.0, -enemy_trait.weak_to.1);
+        if let Some(behavior) = enemy_trait.on_death {
+            f.death_behaviors.add(behavior);
+        }
+
+        // The last trait applied is the one the player can absorb, once the monster is slain.
+        f.essence = Some((family, tier));
     });
 
     monster
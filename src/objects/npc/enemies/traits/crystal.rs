@@ -7,6 +7,8 @@ use super::*;
 pub fn crystal_trait(tier: i32) -> Trait {
     let corpse = String::from("shards");
     let color = tcod::colors::LIGHTER_TURQUOISE;
+    let element = DamageType::Crystal;
+    let weakness = DamageType::Earth;
 
     let weak = Trait {
         name: String::from("Crystal "),
@@ -16,6 +18,11 @@ pub fn crystal_trait(tier: i32) -> Trait {
         power: 0,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Reflect { chance: 15 },
+        resists: (element, 30),
+        weak_to: (weakness, 50),
+        on_death: None,
     };
 
     let mid = Trait {
@@ -26,6 +33,11 @@ pub fn crystal_trait(tier: i32) -> Trait {
         power: 1,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Reflect { chance: 30 },
+        resists: (element, 60),
+        weak_to: (weakness, 35),
+        on_death: None,
     };
 
     let strong = Trait {
@@ -36,6 +48,11 @@ pub fn crystal_trait(tier: i32) -> Trait {
         power: 4,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Reflect { chance: 50 },
+        resists: (element, 90),
+        weak_to: (weakness, 25),
+        on_death: None,
     };
 
     let new_trait = match tier {
@@ -7,6 +7,8 @@ use super::*;
 pub fn death_trait(tier: i32) -> Trait {
     let corpse = String::from("bones");
     let color = tcod::colors::DARKER_FUCHSIA;
+    let element = DamageType::Death;
+    let weakness = DamageType::Light;
 
     let weak = Trait {
         name: String::from("Necro "),
@@ -16,6 +18,11 @@ pub fn death_trait(tier: i32) -> Trait {
         power: 0,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Drain { percent: 25 },
+        resists: (element, 30),
+        weak_to: (weakness, 50),
+        on_death: None,
     };
 
     let mid = Trait {
@@ -26,6 +33,11 @@ pub fn death_trait(tier: i32) -> Trait {
         power: 0,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Drain { percent: 50 },
+        resists: (element, 60),
+        weak_to: (weakness, 35),
+        on_death: None,
     };
 
     let strong = Trait {
@@ -36,6 +48,11 @@ pub fn death_trait(tier: i32) -> Trait {
         power: 8,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Drain { percent: 75 },
+        resists: (element, 90),
+        weak_to: (weakness, 25),
+        on_death: None,
     };
 
     let new_trait = match tier {
@@ -7,6 +7,8 @@ use super::*;
 pub fn earth_trait(tier: i32) -> Trait {
     let corpse = String::from("rubble");
     let color = tcod::colors::Color{ r:98 ,g:74 ,b:10 };
+    let element = DamageType::Earth;
+    let weakness = DamageType::Nature;
 
     let weak = Trait {
         name: String::from("Dirt "),
@@ -16,6 +18,11 @@ pub fn earth_trait(tier: i32) -> Trait {
         power: 0,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Knockback { distance: 1 },
+        resists: (element, 30),
+        weak_to: (weakness, 50),
+        on_death: None,
     };
 
     let mid = Trait {
@@ -26,6 +33,11 @@ pub fn earth_trait(tier: i32) -> Trait {
         power: 2,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Knockback { distance: 2 },
+        resists: (element, 60),
+        weak_to: (weakness, 35),
+        on_death: None,
     };
 
     let strong = Trait {
@@ -36,6 +48,11 @@ pub fn earth_trait(tier: i32) -> Trait {
         power: 3,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Knockback { distance: 3 },
+        resists: (element, 90),
+        weak_to: (weakness, 25),
+        on_death: None,
     };
 
     let new_trait = match tier {
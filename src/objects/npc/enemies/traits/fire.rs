@@ -7,6 +7,8 @@ use super::*;
 pub fn fire_trait(tier: i32) -> Trait {
     let corpse = String::from("embers");
     let color = tcod::colors::AMBER;
+    let element = DamageType::Fire;
+    let weakness = DamageType::Water;
 
     let weak = Trait {
         name: String::from("Warm "),
@@ -16,6 +18,11 @@ pub fn fire_trait(tier: i32) -> Trait {
         power: 2,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Ignite { damage: 1, turns: 2 },
+        resists: (element, 30),
+        weak_to: (weakness, 50),
+        on_death: None,
     };
 
     let mid = Trait {
@@ -26,6 +33,11 @@ pub fn fire_trait(tier: i32) -> Trait {
         power: 3,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Ignite { damage: 2, turns: 3 },
+        resists: (element, 60),
+        weak_to: (weakness, 35),
+        on_death: None,
     };
 
     let strong = Trait {
@@ -36,6 +48,11 @@ pub fn fire_trait(tier: i32) -> Trait {
         power: 7,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: Some(RangedAttack::new(Projectile::FireBolt, 6, 12, 4, None)),
+        ability: Ability::Ignite { damage: 3, turns: 4 },
+        resists: (element, 90),
+        weak_to: (weakness, 25),
+        on_death: Some(DeathBehavior::Explode { damage: 12, radius: 1.5 }),
     };
 
     let new_trait = match tier {
@@ -7,6 +7,8 @@ use super::*;
 pub fn light_trait(tier: i32) -> Trait {
     let corpse = String::from("dust");
     let color = tcod::colors::LIGHT_YELLOW;
+    let element = DamageType::Light;
+    let weakness = DamageType::Death;
 
     let weak = Trait {
         name: String::from("Holy "),
@@ -16,6 +18,11 @@ pub fn light_trait(tier: i32) -> Trait {
         power: 2,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Blind { turns: 2 },
+        resists: (element, 30),
+        weak_to: (weakness, 50),
+        on_death: None,
     };
 
     let mid = Trait {
@@ -26,6 +33,11 @@ pub fn light_trait(tier: i32) -> Trait {
         power: 4,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: Some(RangedAttack::new(Projectile::LightBolt, 7, 7, 4, None)),
+        ability: Ability::Blind { turns: 3 },
+        resists: (element, 60),
+        weak_to: (weakness, 35),
+        on_death: None,
     };
 
     let strong = Trait {
@@ -36,6 +48,11 @@ pub fn light_trait(tier: i32) -> Trait {
         power: 10,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: Some(RangedAttack::new(Projectile::LightBolt, 8, 11, 3, None)),
+        ability: Ability::Blind { turns: 5 },
+        resists: (element, 90),
+        weak_to: (weakness, 25),
+        on_death: None,
     };
 
     let new_trait = match tier {
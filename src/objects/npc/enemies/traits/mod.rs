@@ -21,11 +21,15 @@ pub struct Trait {
     pub power: i32,
     pub color: Color,
     pub corpse_type: String,
+    pub ranged: Option<RangedAttack>,
+    pub ability: Ability,
+    pub resists: (DamageType, i32), // Percentage of damage of this type which is ignored.
+    pub weak_to: (DamageType, i32), // Percentage of extra damage taken from this type.
+    pub on_death: Option<DeathBehavior>,
 }
 
-// Randomly selects, and returns a trait type.
-// Used by random monster generator.
-pub fn get_trait(theme: MapTheme, tier: i32) -> Trait {
+// Randomly selects a trait family, favouring the family which matches the map theme.
+pub fn get_trait_family(theme: MapTheme) -> MapTheme {
 
     let mut trait_chances = [
         Weighted {
@@ -101,7 +105,12 @@ pub fn get_trait(theme: MapTheme, tier: i32) -> Trait {
     ];
     let trait_choice = WeightedChoice::new(&mut trait_chances);
 
-    let new_trait = match trait_choice.ind_sample(&mut rand::thread_rng()) {
+    trait_choice.ind_sample(&mut rand::thread_rng())
+}
+
+// Returns the trait of the given family, at the relevant power level.
+pub fn trait_from_family(family: MapTheme, tier: i32) -> Trait {
+    let new_trait = match family {
         MapTheme::Fire => fire::fire_trait(tier),
         MapTheme::Nature => nature::nature_trait(tier),
         MapTheme::Water => water::water_trait(tier),
@@ -7,6 +7,8 @@ use super::*;
 pub fn nature_trait(tier: i32) -> Trait {
     let corpse = String::from("leaves");
     let color = tcod::colors::GREEN;
+    let element = DamageType::Nature;
+    let weakness = DamageType::Fire;
 
     let weak = Trait {
         name: String::from("Bush "),
@@ -16,6 +18,11 @@ pub fn nature_trait(tier: i32) -> Trait {
         power: 1,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Regenerate { amount: 1 },
+        resists: (element, 30),
+        weak_to: (weakness, 50),
+        on_death: Some(DeathBehavior::Herbs { count: 1 }),
     };
 
     let mid = Trait {
@@ -26,6 +33,11 @@ pub fn nature_trait(tier: i32) -> Trait {
         power: 2,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Regenerate { amount: 2 },
+        resists: (element, 60),
+        weak_to: (weakness, 35),
+        on_death: Some(DeathBehavior::Herbs { count: 1 }),
     };
 
     let strong = Trait {
@@ -36,6 +48,11 @@ pub fn nature_trait(tier: i32) -> Trait {
         power: 5,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: Some(RangedAttack::new(Projectile::Thorn, 5, 9, 1, Some(6))),
+        ability: Ability::Regenerate { amount: 3 },
+        resists: (element, 90),
+        weak_to: (weakness, 25),
+        on_death: Some(DeathBehavior::Herbs { count: 2 }),
     };
 
     let new_trait = match tier {
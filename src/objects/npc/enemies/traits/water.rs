@@ -7,6 +7,8 @@ use super::*;
 pub fn water_trait(tier: i32) -> Trait {
     let corpse = String::from("puddle");
     let color = tcod::colors::LIGHTER_AZURE;
+    let element = DamageType::Water;
+    let weakness = DamageType::Earth;
 
     let weak = Trait {
         name: String::from("Wet "),
@@ -16,6 +18,11 @@ pub fn water_trait(tier: i32) -> Trait {
         power: 1,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Slow { turns: 2 },
+        resists: (element, 30),
+        weak_to: (weakness, 50),
+        on_death: None,
     };
 
     let mid = Trait {
@@ -26,6 +33,11 @@ pub fn water_trait(tier: i32) -> Trait {
         power: 2,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Slow { turns: 3 },
+        resists: (element, 60),
+        weak_to: (weakness, 35),
+        on_death: None,
     };
 
     let strong = Trait {
@@ -36,6 +48,11 @@ pub fn water_trait(tier: i32) -> Trait {
         power: 5,
         color: color,
         corpse_type: corpse.clone(),
+        ranged: None,
+        ability: Ability::Slow { turns: 5 },
+        resists: (element, 90),
+        weak_to: (weakness, 25),
+        on_death: None,
     };
 
     let new_trait = match tier {
@@ -1,8 +1,13 @@
 pub mod enemies;
 pub mod ai;
+pub mod death;
 
-use crate::environment::Game;
+use crate::environment::{ Game, MapTheme };
 use super::Object;
+use super::effects::StatusEffects;
+use ai::Idle;
+
+use rand::Rng;
 
 use serde::{ Serialize, Deserialize };
 
@@ -16,14 +21,217 @@ pub struct Fighter {
     pub hp: i32,
     pub defense: i32,
     pub power: i32,
+    pub accuracy: i32, // Percentage chance of landing a blow on a target which can't dodge.
+    pub evasion: i32, // Taken off the accuracy of anything attacking this character.
+    pub sight: i32, // How far away the character can see.
+    pub idle: Idle,
+    pub fled: bool, // Whether the character has already run away from its current wounds.
+    pub ranged: Option<RangedAttack>,
+    pub uses_items: bool, // Smarter monsters drink potions, and read scrolls, from their inventory.
+    pub ability: Option<Ability>,
+    pub effects: StatusEffects,
+    pub resistances: Resistances,
+    pub essence: Option<(MapTheme, i32)>, // Trait family, and tier, left behind on death.
+    pub death_behaviors: DeathBehaviors,
     pub on_death: DeathCallback,
 }
 
+// The different kinds of damage. Every element belongs to one of the map themes.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Water,
+    Earth,
+    Light,
+    Death,
+    Crystal,
+    Nature,
+}
+
+impl DamageType {
+    // Returns the element of a map theme.
+    pub fn from_theme(theme: MapTheme) -> DamageType {
+        match theme {
+            MapTheme::Fire => DamageType::Fire,
+            MapTheme::Nature => DamageType::Nature,
+            MapTheme::Water => DamageType::Water,
+            MapTheme::Light => DamageType::Light,
+            MapTheme::Death => DamageType::Death,
+            MapTheme::Crystal => DamageType::Crystal,
+            MapTheme::Earth => DamageType::Earth,
+        }
+    }
+
+    // Name of the damage type, for the combat log.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageType::Physical => "blow",
+            DamageType::Fire => "fire",
+            DamageType::Water => "water",
+            DamageType::Earth => "earth",
+            DamageType::Light => "light",
+            DamageType::Death => "death",
+            DamageType::Crystal => "crystal",
+            DamageType::Nature => "nature",
+        }
+    }
+}
+
+// Percentage modifiers to each type of damage, in the same order as DamageType.
+// Positive values resist damage, and negative values are weaknesses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Resistances {
+    values: [i32; 8],
+}
+
+impl Resistances {
+    pub fn get(&self, kind: DamageType) -> i32 {
+        self.values[kind as usize]
+    }
+
+    pub fn add(&mut self, kind: DamageType, amount: i32) {
+        self.values[kind as usize] += amount;
+    }
+}
+
+// Special abilities granted by a monster's trait family.
+// On-hit abilities take effect whenever the monster deals damage, the rest work on their own.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Ability {
+    Ignite { damage: i32, turns: i32 },
+    Slow { turns: i32 },
+    Knockback { distance: i32 },
+    Reflect { chance: i32 }, // Percentage chance of turning a spell back on its caster.
+    Drain { percent: i32 }, // Percentage of the damage dealt which is healed.
+    Blind { turns: i32 },
+    Regenerate { amount: i32 },
+}
+
+impl Ability {
+    // Describes the ability, for the mouse-over name panel.
+    pub fn description(&self) -> String {
+        match self {
+            Ability::Ignite { damage, turns } => format!("ignites for {} x {}", damage, turns),
+            Ability::Slow { turns } => format!("slows for {}", turns),
+            Ability::Knockback { distance } => format!("knocks back {}", distance),
+            Ability::Reflect { chance } => format!("reflects spells {}%", chance),
+            Ability::Drain { percent } => format!("drains {}% life", percent),
+            Ability::Blind { turns } => format!("blinds for {}", turns),
+            Ability::Regenerate { amount } => format!("regenerates {}", amount),
+        }
+    }
+}
+
+// The different things a monster can shoot.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Projectile {
+    Spit,
+    FireBolt,
+    LightBolt,
+    Thorn,
+}
+
+impl Projectile {
+    // Describes the projectile being shot, for the combat log.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Projectile::Spit => "spits acid at",
+            Projectile::FireBolt => "hurls a bolt of fire at",
+            Projectile::LightBolt => "fires a searing beam of light at",
+            Projectile::Thorn => "launches a volley of thorns at",
+        }
+    }
+
+    pub fn damage_type(&self) -> DamageType {
+        match self {
+            Projectile::Spit => DamageType::Physical,
+            Projectile::FireBolt => DamageType::Fire,
+            Projectile::LightBolt => DamageType::Light,
+            Projectile::Thorn => DamageType::Nature,
+        }
+    }
+}
+
+// Ranged attack definition.
+// A cooldown is the number of turns between shots, and ammo limits the number of shots, if there is any.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RangedAttack {
+    pub projectile: Projectile,
+    pub range: i32,
+    pub power: i32,
+    pub cooldown: i32,
+    pub recharge: i32, // Turns left until the next shot.
+    pub ammo: Option<i32>,
+}
+
+impl RangedAttack {
+    pub fn new(projectile: Projectile, range: i32, power: i32, cooldown: i32, ammo: Option<i32>) -> RangedAttack {
+        RangedAttack {
+            projectile: projectile,
+            range: range,
+            power: power,
+            cooldown: cooldown,
+            recharge: 0,
+            ammo: ammo,
+        }
+    }
+
+    // Checks whether the attack is ready to fire, and has ammo left.
+    pub fn ready(&self) -> bool {
+        self.recharge <= 0 && self.ammo.map_or(true, |ammo| ammo > 0)
+    }
+}
+
 // Allows for different death effects based on the enemy killed.
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum DeathCallback {
     Player,
     Monster,
+    Boss,
+}
+
+// Extra things which happen when a monster dies, given by its base monster or its traits.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DeathBehavior {
+    Split { tier: i32 }, // Splits into two blobs of the given tier.
+    Explode { damage: i32, radius: f32 },
+    Herbs { count: i32 }, // Leaves healing herbs behind.
+}
+
+// Every death behavior of a fighter.
+// A monster can get one from its base monster, and one from each trait applied to it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeathBehaviors {
+    slots: [Option<DeathBehavior>; 3],
+}
+
+impl DeathBehaviors {
+    pub fn new(behavior: DeathBehavior) -> DeathBehaviors {
+        let mut behaviors = DeathBehaviors::default();
+        behaviors.add(behavior);
+        behaviors
+    }
+
+    // Adds a behavior to the first free slot. Any past the last slot are ignored.
+    pub fn add(&mut self, behavior: DeathBehavior) {
+        if let Some(slot) = self.slots.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(behavior);
+        }
+    }
+
+    pub fn active(&self) -> Vec<DeathBehavior> {
+        self.slots.iter().filter_map(|slot| *slot).collect()
+    }
+}
+
+// A death behavior waiting to be carried out, where the monster fell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Remains {
+    pub x: i32,
+    pub y: i32,
+    pub behavior: DeathBehavior,
+    pub family: Option<MapTheme>, // Trait family of the slain monster, passed on to anything it splits into.
 }
 
 // Connects to the specific callback function of the object
@@ -33,12 +241,55 @@ impl DeathCallback {
         let callback: fn(&mut Object, &mut Game) = match self {
             DeathCallback::Player => Object::fake_player_death,
             DeathCallback::Monster => Object::monster_death,
+            DeathCallback::Boss => Object::boss_death,
         };
         callback(object, game);
     }
 }
 
 impl Object {
+    // Adjusts damage for the resistances of this object. Resistances can't go past full immunity.
+    pub fn resisted_damage(&self, damage: i32, kind: DamageType) -> i32 {
+        let modifier = self.fighter.map_or(0, |f| f.resistances.get(kind)).min(100);
+        if damage <= 0 || modifier == 0 {
+            return damage;
+        }
+        damage * (100 - modifier) / 100
+    }
+
+    // Reports any resistance or weakness to damage this object is taking.
+    // The damage has already been adjusted, so only the reason is given here.
+    pub fn report_resistance(&self, damage: i32, kind: DamageType, game: &mut Game) {
+        let modifier = self.fighter.map_or(0, |f| f.resistances.get(kind));
+        if modifier == 0 || !self.seen_by_player(game) {
+            return;
+        }
+        if damage <= 0 {
+            game.messages.add(format!("{} shrugs off the {}!", self.name, kind.name()), LIGHT_GREY);
+        } else if modifier > 0 {
+            game.messages.add(format!("{} resists the {}.", self.name, kind.name()), LIGHT_GREY);
+        } else {
+            game.messages.add(format!("{} is weak to {}!", self.name, kind.name()), ORANGE);
+        }
+    }
+
+    pub fn is_player(&self) -> bool {
+        self.fighter.map_or(false, |f| f.on_death == DeathCallback::Player)
+    }
+
+    // Checks if the player can see this character, so messages about it are worth showing.
+    pub fn seen_by_player(&self, game: &Game) -> bool {
+        self.is_player() || game.map[self.x as usize][self.y as usize].visible
+    }
+
+    // Rolls to see whether a spell cast at this object is turned back on its caster.
+    pub fn reflects_spell(&self) -> bool {
+        match self.fighter.and_then(|f| f.ability) {
+            Some(Ability::Reflect { chance }) => rand::thread_rng().gen_range(0, 100) < chance,
+            _ => false,
+        }
+    }
+
     // Player death functions quite differently from other objects.
     // However, the player is still an object, so a dummy-function is used to fill that gap.
     fn fake_player_death(_object: &mut Object, _game: &mut Game) {}
@@ -53,10 +304,35 @@ impl Object {
                 monster.fighter.unwrap().exp
             ),
             DARK_RED);
+
+        // The essence of the monster's trait is left behind, for the player to absorb.
+        if let Some(essence) = monster.fighter.and_then(|f| f.essence) {
+            game.essences.push(essence);
+        }
+
+        // Death behaviors are carried out once the turn is over, as they can affect the rest of the map.
+        if let Some(fighter) = monster.fighter {
+            for behavior in fighter.death_behaviors.active() {
+                game.remains.push(Remains {
+                    x: monster.x,
+                    y: monster.y,
+                    behavior: behavior,
+                    family: fighter.essence.map(|(family, _)| family),
+                });
+            }
+        }
+
         monster.color = DARK_RED;
         monster.blocks = false;
         monster.fighter = None;
         monster.ai = None;
         monster.name = format!("{}{}", monster.name, monster.corpse_type);
     }
+
+    // Bosses die like any other monster, but their death also unseals the stairs.
+    fn boss_death(boss: &mut Object, game: &mut Game) {
+        Object::monster_death(boss, game);
+        game.stairs_sealed = false;
+        game.messages.add("With its master slain, the seal on the stairs shatters!", GOLD);
+    }
 }
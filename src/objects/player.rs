@@ -1,9 +1,12 @@
-use crate::{ LEVEL_UP_FACTOR, LEVEL_UP_BASE, LEVEL_SCREEN_WIDTH };
+use crate::{ LEVEL_UP_FACTOR, LEVEL_UP_BASE, LEVEL_SCREEN_WIDTH, COMBAT_NOISE };
 use crate::Tcod;
-use crate::environment::{ Game };
+use crate::environment::{ Game, MAP_WIDTH, MAP_HEIGHT, TORCH_RADIUS, BLIND_RADIUS, FOV_LIGHT_WALLS, FOV_ALGO };
 
 use super::{ Object, Character };
-use super::npc::{ Fighter, DeathCallback };
+use super::npc::{ Fighter, Resistances, DamageType, DeathCallback, DeathBehaviors };
+use super::npc::ai::Idle;
+use super::effects::{ StatusEffects, EffectKind };
+use super::combat::{ Attack, resolve };
 use super::items::*;
 use crate::graphics::gui::menu::menu;
 
@@ -31,10 +34,24 @@ impl Object {
                     hp: 100,
                     defense: 1,
                     power: 4,
+                    accuracy: 90,
+                    evasion: 10,
+                    sight: TORCH_RADIUS,
+                    idle: Idle::Still,
+                    fled: false,
+                    ranged: None,
+                    uses_items: true,
+                    ability: None,
+                    effects: StatusEffects::default(),
+                    resistances: Resistances::default(),
+                    essence: None,
+                    death_behaviors: DeathBehaviors::default(),
                     on_death: DeathCallback::Player,
                 }),
                 ai: None,
                 item: None,
+                trap: None,
+                equipment: None,
                 level: 1,
                 always_visible: false,
             },
@@ -44,6 +61,14 @@ impl Object {
 
     // Decides if the player object should move, or attack when inputs are entered.
     pub fn player_move_or_attack(dx: i32, dy: i32, game: &mut Game, characters: &mut [Character], player: &mut Object) {
+        // A confused player stumbles off in a random direction half of the time.
+        let (dx, dy) = if player.has_effect(EffectKind::Confusion) && rand::thread_rng().gen_range(0, 2) == 0 {
+            game.messages.add("You stumble around in confusion.", LIGHTER_HAN);
+            (rand::thread_rng().gen_range(-1, 2), rand::thread_rng().gen_range(-1, 2))
+        } else {
+            (dx, dy)
+        };
+
         // The coordinates the player is moving to / attacking
         let x = player.x + dx;
         let y = player.y + dy;
@@ -54,29 +79,28 @@ impl Object {
         // Attack target if found, otherwise move
         match target_id {
             Some(target_id) => {
-                let damage = Object::player_attack(&mut characters[target_id].object, player);
-                if damage > 0 {
-                    // Target takes damage.
-                    game.messages.add(
-                        format!(
-                            "{} attacks {} dealing {} damage.",
-                            player.name, characters[target_id].object.name, damage
-                        ),
-                        player.color,
-                    );
+                // A frightened player can't bring themselves to fight.
+                if player.has_effect(EffectKind::Fear) {
+                    game.messages.add("You are too frightened to attack!", DARKER_PURPLE);
+                    return;
+                }
+
+                // The sounds of combat carry.
+                game.make_noise(x, y, COMBAT_NOISE);
+                let result = resolve(&Attack::melee(player, Object::weapon_element(game)), &characters[target_id].object);
+                game.stats.record_dealt(&result);
+                game.messages.add(
+                    result.describe(&player.name, "attacks", &characters[target_id].object.name),
+                    result.color(player.color),
+                );
+
+                if result.landed() {
                     // Applies exp to player, if needed.
-                    if let Some(exp) = characters[target_id].object.take_damage(damage, game) {
+                    if let Some(exp) = characters[target_id].object.take_damage(result.damage, result.kind, game) {
                         player.fighter.as_mut().unwrap().exp += exp;
                     }
-                } else {
-                    // Attack was not powerful enough to do damage.
-                    game.messages.add(
-                        format!(
-                            "{} attacks {} but it has no effect!",
-                            player.name, characters[target_id].object.name
-                        ),
-                        WHITE,
-                    );
+                    // Powers taken from slain monsters add their own effects to the blow.
+                    Object::power_on_hit(target_id, result.damage, game, characters, player);
                 }
             },
             // Moves player
@@ -88,21 +112,9 @@ impl Object {
         }
     }
 
-    // Function to allow fighter-enabled objects to attack other fighter-enabled objects.
-    fn player_attack(target: &mut Object, player: &Object) -> i32{
-        // Damage formula.
-        let mut rng = rand::thread_rng();
-        let attack = (player.fighter.map_or(0, |f| f.power)) as f32 + rng.gen_range(-1.0, 1.0);
-        let defense = (target.fighter.map_or(0, |f| f.defense)) as f32 + rng.gen_range(-1.0, 1.0);
-        let mut level_mod = ((player.level - target.level) / 3) as f32;
-        if level_mod <= 0.0 { level_mod = 1.0; }
-
-        let damage = ((attack * level_mod) - defense).round() as i32;
-        damage
-    }
-
-    pub fn player_damage(damage: i32, game: &mut Game, player: &mut Object) {
-        // Apply damage if possible.
+    pub fn player_damage(damage: i32, kind: DamageType, game: &mut Game, player: &mut Object) {
+        // Apply damage if possible. Resistances and weaknesses have already been taken into account.
+        player.report_resistance(damage, kind, game);
         if let Some(fighter) = player.fighter.as_mut() {
             if damage > 0 {
                 fighter.hp -= damage;
@@ -189,7 +201,7 @@ impl Object {
                     // If item is pulled successfully, it is inserted into the players inventory
                     Some(pick_up_item) => {
                         game.messages.add(
-                            format!("You picked found a {}", pick_up_item.name), // Message is displayed as such.
+                            format!("You picked found a {}", game.identity.name_of(&pick_up_item)), // Message is displayed as such.
                             pick_up_item.color,
                         );
                         inventory.push(pick_up_item);
@@ -217,24 +229,29 @@ impl Object {
             // Inventory is found on player.
             Some(inventory) => {
                 // Finds item within the inventory
-                if let Some(item) = inventory[inventory_id].item {
-                    // Depending on the type of item, a variable is assigned a function.
-                    let on_use = match item {
-                        Item::Heal => Object::use_health_potion,
-                        Item::LightningBoltScroll => Object::use_lightning_bolt_scroll,
-                        Item::ConfusionScroll => Object::use_confusion_scroll,
-                        Item::FireballScroll => Object::use_fireball_scroll,
-                        Item::FearScroll => Object::use_fear_scroll,
-                        Item::HpUp => Object::use_health_up,
-                        Item::PowUp => Object::use_power_up,
-                        Item::DefUp => Object::use_defense_up,
-                    };
-                    // Triggers the relevant item usage function, and decides what to do depending on how the item works.
-                    match on_use(inventory_id, tcod, game, &mut player.object, characters, items) {
+                if let Some(item) = inventory[inventory_id].item.clone() {
+                    let name = inventory[inventory_id].name.clone();
+                    let color = inventory[inventory_id].color;
+                    // Applies the effects of the item, and decides what to do depending on how the item works.
+                    match Object::use_item_effects(&item, &name, color, tcod, game, &mut player.object, characters, items) {
                         // If the item is used successfully, and is to be erased afterwards.
                         UseResult::UsedUp => {
                             // Destroy after use, unless it was cancelled for some reason.
                             inventory.remove(inventory_id);
+                            // Using an item shows what it is, and identify scrolls reveal whatever else is carried.
+                            Object::learn_item(&name, game);
+                            for effect in item.effects.iter() {
+                                if let ItemEffect::Identify { count } = *effect {
+                                    Object::identify_carried(count, inventory, game);
+                                }
+                            }
+                        },
+                        // Equipment is worn, and anything it replaces goes back into the inventory.
+                        UseResult::Equip => {
+                            let item = inventory.remove(inventory_id);
+                            if let Some(removed) = Object::equip(item, game, &mut player.object) {
+                                inventory.push(removed);
+                            }
                         },
                         // Item fails to be used.
                         UseResult::Cancelled => {
@@ -265,19 +282,10 @@ impl Object {
 
                 // Sets item position to the player position.
                 item.set_pos(x, y);
-                game.messages.add(format!("You dropped a {}.", item.name), YELLOW);
-
-                // Loops to find an empty key in the item hashmap.
-                let mut new_id = 1;
-                for _ in 0..items.len() {
-                    if items.contains_key(&new_id) {
-                        new_id += 1;
-                    } else {
-                        break;
-                    }
-                }
-                // Inserts the item into the hashmap with its new id.
-                items.insert(new_id, item);
+                game.messages.add(format!("You dropped a {}.", game.identity.name_of(&item)), YELLOW);
+
+                // Inserts the item into the hashmap with a free id.
+                items.insert(Object::free_item_id(items), item);
             },
             // Do nothing if the inventory is inaccessible.
             _ => (()),
@@ -312,4 +320,69 @@ impl Object {
         let dy = self.y - y;
         ((dx.pow(2) + dy.pow(2)) as f32).sqrt()
     }
+
+    // How far the player can see. Blinded players can barely see past the end of their nose.
+    pub fn sight_radius(&self) -> i32 {
+        if self.has_effect(EffectKind::Blindness) {
+            BLIND_RADIUS
+        } else {
+            TORCH_RADIUS
+        }
+    }
+
+    // Checks the tiles surrounding the player for secret passages.
+    // Each hidden tile has a chance to be discovered, and true is returned if any were found.
+    pub fn search_for_secrets(tcod: &mut Tcod, game: &mut Game, player: &Object, chance: f32) -> bool {
+        let mut discovered = false;
+
+        for x in (player.x - 1)..(player.x + 2) {
+            for y in (player.y - 1)..(player.y + 2) {
+                // Ignores any tiles outside of the map boundary.
+                if x < 0 || y < 0 || x >= MAP_WIDTH || y >= MAP_HEIGHT {
+                    continue;
+                }
+
+                // Only undiscovered secret paths can be found, and only if the roll succeeds.
+                let tile = &mut game.map[x as usize][y as usize];
+                if tile.secret_path && !tile.found && rand::thread_rng().gen::<f32>() < chance {
+                    tile.discover();
+                    // The FOV map is updated, so the path can be seen through and walked on.
+                    tcod.fov.set(x, y, true, true);
+                    discovered = true;
+                }
+            }
+        }
+
+        if discovered {
+            game.messages.add("You discover a hidden passage!", LIGHT_AZURE);
+            // Recomputes the FOV so that anything behind the passage becomes visible.
+            tcod.fov.compute_fov(player.x, player.y, player.sight_radius(), FOV_LIGHT_WALLS, FOV_ALGO);
+        }
+        discovered
+    }
+
+    // Prays at the altar beneath the player, restoring them to full health.
+    // Each altar can only answer a single prayer, and true is returned if one was answered.
+    pub fn pray_at_altar(game: &mut Game, items: &mut HashMap<i32, Object>, player: &mut Object) -> bool {
+        let altar = items
+            .values_mut()
+            .find(|obj| obj.pos() == player.pos() && obj.name == "Altar");
+
+        match altar {
+            Some(altar) => {
+                game.messages.add("You kneel at the altar, and a warm light washes over you.", LIGHTEST_YELLOW);
+                let max_hp = player.fighter.map_or(0, |f| f.max_hp);
+                player.heal(max_hp);
+
+                // The altar falls silent after it has been used.
+                altar.name = "Dormant altar".into();
+                altar.color = DARK_GREY;
+                true
+            },
+            None => {
+                game.messages.add("There is no altar here to pray at.", RED);
+                false
+            },
+        }
+    }
 }
@@ -0,0 +1,285 @@
+use crate::environment::{ Game, MapTheme };
+
+use super::{ Object, Character };
+use super::npc::{ Ability, DamageType, Resistances };
+use super::npc::enemies::traits::trait_from_family;
+use super::effects::EffectKind;
+
+use rand::Rng;
+
+use serde::{ Serialize, Deserialize };
+
+use tcod::colors::*;
+
+// How many powers the player can hold at once.
+pub const POWER_SLOTS: usize = 3;
+
+// Essence needed to raise a power by a single tier, and the highest tier a power can reach.
+const ESSENCE_PER_TIER: i32 = 4;
+const MAX_TIER: i32 = 3;
+
+// A power absorbed from slain monsters.
+// Each power follows the trait of the same family and tier, giving its resistance and ability to the player.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Power {
+    pub family: MapTheme,
+    pub tier: i32,
+    pub essence: i32, // Essence gathered towards the next tier.
+}
+
+impl Power {
+    pub fn new(family: MapTheme) -> Power {
+        Power {
+            family: family,
+            tier: 1,
+            essence: 0,
+        }
+    }
+
+    // Adds essence, raising the tier whenever enough has been gathered.
+    // Returns true if the power went up a tier.
+    fn absorb(&mut self, amount: i32) -> bool {
+        let old_tier = self.tier;
+        self.essence += amount;
+        while self.tier < MAX_TIER && self.essence >= ESSENCE_PER_TIER {
+            self.essence -= ESSENCE_PER_TIER;
+            self.tier += 1;
+        }
+        if self.tier == MAX_TIER {
+            self.essence = 0;
+        }
+        self.tier > old_tier
+    }
+
+    // Powers are named after the trait they come from, such as "Blazing affinity".
+    pub fn name(&self) -> String {
+        format!("{}affinity", trait_from_family(self.family, self.tier).name)
+    }
+
+    pub fn ability(&self) -> Ability {
+        trait_from_family(self.family, self.tier).ability
+    }
+
+    pub fn resistance(&self) -> (DamageType, i32) {
+        trait_from_family(self.family, self.tier).resists
+    }
+
+    // Describes the power, for the powers screen.
+    pub fn description(&self) -> String {
+        let (element, amount) = self.resistance();
+        let progress = if self.tier < MAX_TIER {
+            format!(" [{}/{}]", self.essence, ESSENCE_PER_TIER)
+        } else {
+            String::new()
+        };
+        format!(
+            "{} {}: {} {}%, {}{}",
+            self.name(), self.tier, element.name(), amount, self.ability().description(), progress
+        )
+    }
+}
+
+impl Object {
+    // Absorbs the essences left behind by the monsters slain this turn.
+    // Essence of a family the player already holds strengthens it, otherwise it takes up a free slot.
+    pub fn absorb_essences(game: &mut Game, player: &mut Object) {
+        let essences: Vec<(MapTheme, i32)> = game.essences.drain(..).collect();
+        for (family, tier) in essences {
+            if let Some(power) = game.powers.iter_mut().find(|power| power.family == family) {
+                if power.absorb(tier) {
+                    let name = power.name();
+                    game.messages.add(format!("Your power grows into {}!", name), GOLD);
+                }
+                continue;
+            }
+
+            let power = Power::new(family);
+            if game.powers.len() < POWER_SLOTS {
+                game.messages.add(format!("You absorb the essence of the slain, and gain {}!", power.name()), GOLD);
+                game.powers.push(power);
+            } else {
+                game.messages.add(
+                    format!("You have no room for {}. Press 'e' to manage your powers.", power.name()),
+                    LIGHT_GREY,
+                );
+                game.unclaimed = Some(power);
+            }
+        }
+        Object::refresh_resistances(game, player);
+    }
+
+    // Swaps a power out for the unclaimed one, or simply releases it if there isn't one.
+    pub fn replace_power(index: usize, game: &mut Game, player: &mut Object) {
+        if index >= game.powers.len() {
+            return;
+        }
+
+        let released = game.powers[index].name();
+        match game.unclaimed.take() {
+            Some(power) => {
+                game.messages.add(format!("You release {}, and absorb {}.", released, power.name()), GOLD);
+                game.powers[index] = power;
+            },
+            None => {
+                game.messages.add(format!("You release {}.", released), LIGHT_GREY);
+                game.powers.remove(index);
+            },
+        }
+        Object::refresh_resistances(game, player);
+    }
+
+    // Recalculates the resistances the player gains from their powers, and the equipment they're wearing.
+    pub fn refresh_resistances(game: &Game, player: &mut Object) {
+        if let Some(fighter) = player.fighter.as_mut() {
+            fighter.resistances = Resistances::default();
+            for power in &game.powers {
+                let (element, amount) = power.resistance();
+                fighter.resistances.add(element, amount);
+            }
+            for equipment in game.equipment.iter().filter_map(|item| item.equipment) {
+                if let Some((element, amount)) = equipment.resistance {
+                    fighter.resistances.add(element, amount);
+                }
+            }
+        }
+    }
+
+    // Applies the abilities of the player's powers to a monster they've just hit.
+    pub fn power_on_hit(target_id: usize, damage: i32, game: &mut Game, characters: &mut [Character], player: &mut Object) {
+        for power in game.powers.clone() {
+            // Nothing more happens to a monster which has already been slain.
+            if damage <= 0 || characters[target_id].object.fighter.is_none() {
+                return;
+            }
+
+            match power.ability() {
+                Ability::Ignite { damage: burn_damage, turns } => {
+                    characters[target_id].object.add_effect(EffectKind::Burning, turns, burn_damage, game);
+                },
+                Ability::Slow { turns } => characters[target_id].object.add_effect(EffectKind::Slow, turns, 0, game),
+                Ability::Blind { turns } => characters[target_id].object.add_effect(EffectKind::Blindness, turns, 0, game),
+                Ability::Drain { percent } => {
+                    let drained = (damage * percent / 100).max(1);
+                    player.heal(drained);
+                    game.messages.add(
+                        format!("{} drains {} life from {}.", player.name, drained, characters[target_id].object.name),
+                        DARK_PURPLE,
+                    );
+                },
+
+                // The monster is pushed directly away from the player, until it hits something.
+                Ability::Knockback { distance } => {
+                    let (dx, dy) = (
+                        (characters[target_id].object.x - player.x).signum(),
+                        (characters[target_id].object.y - player.y).signum(),
+                    );
+                    let mut pushed = 0;
+                    while pushed < distance {
+                        let (x, y) = (characters[target_id].object.x + dx, characters[target_id].object.y + dy);
+                        if Object::is_blocked(x, y, &game.map, characters) {
+                            break;
+                        }
+                        characters[target_id].object.set_pos(x, y);
+                        pushed += 1;
+                    }
+                    if pushed > 0 {
+                        game.messages.add(format!("{} is knocked back!", characters[target_id].object.name), DARK_AMBER);
+                    }
+                },
+
+                // Reflection and regeneration don't do anything on a hit.
+                Ability::Reflect { .. } | Ability::Regenerate { .. } => {},
+            }
+        }
+    }
+
+    // Runs the passive abilities of the player's powers, once per turn.
+    pub fn powers_turn(game: &Game, player: &mut Object) {
+        for power in &game.powers {
+            if let Ability::Regenerate { amount } = power.ability() {
+                player.heal(amount);
+            }
+        }
+    }
+
+    // Rolls to see whether the player's powers turn aside a projectile.
+    pub fn deflects_projectile(game: &Game) -> bool {
+        game.powers.iter().any(|power| match power.ability() {
+            Ability::Reflect { chance } => rand::thread_rng().gen_range(0, 100) < chance,
+            _ => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn families(game: &Game) -> Vec<MapTheme> {
+        game.powers.iter().map(|power| power.family).collect()
+    }
+
+    #[test]
+    fn absorb_raises_tiers_and_stops_at_the_max() {
+        let mut power = Power::new(MapTheme::Fire);
+        assert!(!power.absorb(ESSENCE_PER_TIER - 1));
+        assert_eq!((power.tier, power.essence), (1, ESSENCE_PER_TIER - 1));
+
+        assert!(power.absorb(1));
+        assert_eq!((power.tier, power.essence), (2, 0));
+
+        assert!(power.absorb(ESSENCE_PER_TIER * 5));
+        assert_eq!((power.tier, power.essence), (MAX_TIER, 0));
+        assert!(!power.absorb(ESSENCE_PER_TIER));
+    }
+
+    #[test]
+    fn essences_fill_free_slots_then_wait_unclaimed() {
+        let mut game = Game::for_tests();
+        let mut player = Object::new_player().object;
+        game.essences = vec![
+            (MapTheme::Fire, 1),
+            (MapTheme::Water, 1),
+            (MapTheme::Earth, 1),
+            (MapTheme::Death, 1),
+        ];
+
+        Object::absorb_essences(&mut game, &mut player);
+        assert_eq!(families(&game), vec![MapTheme::Fire, MapTheme::Water, MapTheme::Earth]);
+        assert_eq!(game.unclaimed, Some(Power::new(MapTheme::Death)));
+        assert!(game.essences.is_empty());
+    }
+
+    #[test]
+    fn essence_of_a_held_family_strengthens_it() {
+        let mut game = Game::for_tests();
+        let mut player = Object::new_player().object;
+        game.powers = vec![Power::new(MapTheme::Fire)];
+        game.essences = vec![(MapTheme::Fire, 2)];
+
+        Object::absorb_essences(&mut game, &mut player);
+        assert_eq!(game.powers.len(), 1);
+        assert_eq!(game.powers[0].essence, 2);
+        assert_eq!(game.unclaimed, None);
+    }
+
+    #[test]
+    fn replacing_a_power_swaps_in_the_unclaimed_one() {
+        let mut game = Game::for_tests();
+        let mut player = Object::new_player().object;
+        game.powers = vec![Power::new(MapTheme::Fire), Power::new(MapTheme::Water)];
+        game.unclaimed = Some(Power::new(MapTheme::Light));
+
+        Object::replace_power(0, &mut game, &mut player);
+        assert_eq!(families(&game), vec![MapTheme::Light, MapTheme::Water]);
+        assert_eq!(game.unclaimed, None);
+
+        // With nothing waiting, the power is simply released.
+        Object::replace_power(1, &mut game, &mut player);
+        assert_eq!(families(&game), vec![MapTheme::Light]);
+
+        // Slots which are out of range are left alone.
+        Object::replace_power(5, &mut game, &mut player);
+        assert_eq!(families(&game), vec![MapTheme::Light]);
+    }
+}
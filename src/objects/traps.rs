@@ -0,0 +1,474 @@
+use crate::{ FovMap, DISARM_CHANCE, TRAP_NOISE, ALARM_NOISE };
+use crate::environment::{ Game, MapTheme, MAP_WIDTH, MAP_HEIGHT };
+use crate::objects::npc::DamageType;
+use crate::objects::effects::EffectKind;
+use crate::objects::combat::{ Attack, AttackResult, Outcome, resolve };
+use crate::objects::npc::enemies::{ generate_monster, monster_level_up };
+
+use super::{ Object, Character };
+
+use std::collections::HashMap;
+use rand::Rng;
+
+use serde::{ Serialize, Deserialize };
+
+use tcod::colors::*;
+
+// Chance of a poison dart hitting, before the victim's evasion.
+const DART_ACCURACY: i32 = 80;
+
+// The different effects a trap can have.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TrapKind {
+    Pit,
+    Teleport,
+    Alarm,
+    PoisonDart,
+    ElementalBurst,
+}
+
+// Trap definition.
+// Traps scale with the depth they were placed at, and elemental bursts take on the theme of the map.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trap {
+    pub kind: TrapKind,
+    pub hidden: bool,
+    pub level: u32,
+    pub theme: MapTheme,
+}
+
+impl Object {
+    // Generic trap constructor.
+    pub fn new_trap(x: i32, y: i32, kind: TrapKind, level: u32, theme: MapTheme) -> Object {
+        let (name, color) = match kind {
+            TrapKind::Pit => ("Pit trap", DARK_SEPIA),
+            TrapKind::Teleport => ("Teleport trap", LIGHT_MAGENTA),
+            TrapKind::Alarm => ("Alarm trap", LIGHT_YELLOW),
+            TrapKind::PoisonDart => ("Poison dart trap", LIME),
+            TrapKind::ElementalBurst => match theme {
+                MapTheme::Fire => ("Fire rune", FLAME),
+                MapTheme::Nature => ("Thorn rune", GREEN),
+                MapTheme::Water => ("Water rune", AZURE),
+                MapTheme::Light => ("Light rune", LIGHTEST_YELLOW),
+                MapTheme::Death => ("Death rune", PURPLE),
+                MapTheme::Crystal => ("Crystal rune", CYAN),
+                MapTheme::Earth => ("Earth rune", SEPIA),
+            },
+        };
+
+        Object {
+            x: x,
+            y: y,
+            char: '^',
+            color: color,
+            name: name.into(),
+            blocks: false,
+            alive: false,
+            corpse_type: name.into(),
+            fighter: None,
+            ai: None,
+            item: None,
+            trap: Some(Trap {
+                kind: kind,
+                hidden: true,
+                level: level,
+                theme: theme,
+            }),
+            equipment: None,
+            level: 1,
+            always_visible: true,
+        }
+    }
+
+    // Finds the id of any trap located at the given coordinates.
+    pub fn trap_at(x: i32, y: i32, items: &HashMap<i32, Object>) -> Option<i32> {
+        items
+            .iter()
+            .find(|(_, obj)| obj.trap.is_some() && obj.pos() == (x, y))
+            .map(|(id, _)| *id)
+    }
+
+    // Sets off a trap.
+    // A victim of None refers to the player, otherwise it is the id of the character who stepped on it.
+    pub fn spring_trap(
+        trap_id: i32,
+        victim: Option<usize>,
+        fov: &FovMap,
+        game: &mut Game,
+        characters: &mut Vec<Character>,
+        items: &mut HashMap<i32, Object>,
+        player: &mut Object,
+    ) {
+        let (trap, x, y) = match items.get(&trap_id) {
+            Some(obj) => match obj.trap {
+                Some(trap) => (trap, obj.x, obj.y),
+                None => return,
+            },
+            None => return,
+        };
+
+        // Traps sprung by the player, or within sight of them, are revealed.
+        let seen = victim.is_none() || fov.is_in_fov(x, y);
+        if seen {
+            items.get_mut(&trap_id).map(|obj| obj.trap.as_mut().map(|t| t.hidden = false));
+        }
+
+        // Every trap makes a racket, but alarms can be heard from much further away.
+        let noise = if trap.kind == TrapKind::Alarm { ALARM_NOISE } else { TRAP_NOISE };
+        game.make_noise(x, y, noise);
+
+        let victim_name = match victim {
+            Some(id) => characters[id].object.name.clone(),
+            None => player.name.clone(),
+        };
+
+        match trap.kind {
+            TrapKind::Pit => {
+                let attack = Attack::flat(4 + trap.level as i32, DamageType::Physical);
+                let result = Object::trap_attack(victim, &attack, game, characters, player);
+                if seen {
+                    game.messages.add(
+                        format!("{} falls into a pit, taking {} damage!", victim_name, result.damage),
+                        DARK_SEPIA,
+                    );
+                }
+                Object::trap_damage(victim, &result, game, characters, player);
+            },
+
+            TrapKind::Teleport => {
+                // Picks a random empty tile to send the victim to.
+                // If there's nowhere to go, the trap fizzles out.
+                let mut free_tiles = vec![];
+                for tile_x in 1..MAP_WIDTH - 1 {
+                    for tile_y in 1..MAP_HEIGHT - 1 {
+                        if game.map[tile_x as usize][tile_y as usize].empty &&
+                        !Object::is_blocked(tile_x, tile_y, &game.map, characters) &&
+                        player.pos() != (tile_x, tile_y) {
+                            free_tiles.push((tile_x, tile_y));
+                        }
+                    }
+                }
+                if free_tiles.is_empty() {
+                    if seen {
+                        game.messages.add("The teleport trap flickers, but nothing happens.", LIGHT_MAGENTA);
+                    }
+                    return;
+                }
+                let (new_x, new_y) = free_tiles[rand::thread_rng().gen_range(0, free_tiles.len())];
+                if seen {
+                    game.messages.add(
+                        format!("{} vanishes in a flash of light!", victim_name),
+                        LIGHT_MAGENTA,
+                    );
+                }
+                match victim {
+                    Some(id) => characters[id].object.set_pos(new_x, new_y),
+                    None => player.set_pos(new_x, new_y),
+                }
+            },
+
+            TrapKind::Alarm => {
+                // Alarms can be heard from anywhere, and only ring once.
+                game.messages.add(
+                    "A shrill alarm rings out, and something comes to investigate!",
+                    LIGHT_YELLOW,
+                );
+                items.remove(&trap_id);
+                Object::summon_guards(x, y, trap, game, characters, player);
+            },
+
+            TrapKind::PoisonDart => {
+                // Darts can be dodged.
+                let attack = Attack::flat(2 + trap.level as i32, DamageType::Nature).with_accuracy(DART_ACCURACY);
+                let result = Object::trap_attack(victim, &attack, game, characters, player);
+                if result.outcome == Outcome::Miss {
+                    if seen {
+                        game.messages.add(format!("A poison dart flies past {}.", victim_name), LIME);
+                    }
+                    return;
+                }
+
+                if seen {
+                    game.messages.add(
+                        format!("A poison dart strikes {}, dealing {} damage!", victim_name, result.damage),
+                        LIME,
+                    );
+                }
+                Object::trap_damage(victim, &result, game, characters, player);
+
+                // The poison lingers on after the dart has struck.
+                let poison = 1 + trap.level as i32 / 4;
+                match victim {
+                    Some(id) => characters[id].object.add_effect(EffectKind::Poison, 5, poison, game),
+                    None => player.add_effect(EffectKind::Poison, 5, poison, game),
+                }
+            },
+
+            TrapKind::ElementalBurst => {
+                let attack = Attack::flat(6 + 2 * trap.level as i32, DamageType::from_theme(trap.theme));
+                let burst = match trap.theme {
+                    MapTheme::Fire => "A burst of flame",
+                    MapTheme::Nature => "A spray of thorns",
+                    MapTheme::Water => "A jet of scalding water",
+                    MapTheme::Light => "A blinding flash",
+                    MapTheme::Death => "A wave of necrotic energy",
+                    MapTheme::Crystal => "A shower of crystal shards",
+                    MapTheme::Earth => "A shower of falling rocks",
+                };
+                if seen {
+                    game.messages.add(
+                        format!("{} erupts from the {}!", burst, items[&trap_id].name),
+                        items[&trap_id].color,
+                    );
+                }
+
+                // Everything standing next to the trap is caught in the burst.
+                let color = items[&trap_id].color;
+                let mut caught: Vec<Option<usize>> = (0..characters.len())
+                    .filter(|id| characters[*id].object.fighter.is_some() && characters[*id].object.distance(x, y) < 2.0)
+                    .map(Some)
+                    .collect();
+                if player.distance(x, y) < 2.0 {
+                    caught.push(None);
+                }
+
+                for victim in caught {
+                    let result = Object::trap_attack(victim, &attack, game, characters, player);
+                    let name = match victim {
+                        Some(id) => characters[id].object.name.clone(),
+                        None => player.name.clone(),
+                    };
+                    if seen {
+                        game.messages.add(format!("{} is caught in the burst, taking {} damage!", name, result.damage), color);
+                    }
+                    Object::trap_damage(victim, &result, game, characters, player);
+                }
+            },
+        }
+    }
+
+    // Works out how a trap's attack plays out against its victim.
+    fn trap_attack(victim: Option<usize>, attack: &Attack, game: &mut Game, characters: &[Character], player: &Object) -> AttackResult {
+        match victim {
+            Some(id) => resolve(attack, &characters[id].object),
+            None => {
+                let result = resolve(attack, player);
+                game.stats.record_received(&result);
+                result
+            },
+        }
+    }
+
+    // Damages the victim of a trap.
+    // Any monster killed by a trap still awards experience to the player.
+    fn trap_damage(victim: Option<usize>, result: &AttackResult, game: &mut Game, characters: &mut [Character], player: &mut Object) {
+        if !result.landed() {
+            return;
+        }
+        match victim {
+            Some(id) => {
+                if let Some(exp) = characters[id].object.take_damage(result.damage, result.kind, game) {
+                    player.fighter.as_mut().map(|f| f.exp += exp);
+                }
+            },
+            None => Object::player_damage(result.damage, result.kind, game, player),
+        }
+    }
+
+    // Spawns a couple of monsters close to an alarm trap.
+    fn summon_guards(x: i32, y: i32, trap: Trap, game: &Game, characters: &mut Vec<Character>, player: &Object) {
+        let guards = rand::thread_rng().gen_range(1, 3);
+        let max_tries = 50;
+
+        for _ in 0..guards {
+            for _ in 0..max_tries {
+                // Picks a random tile, a short distance away from the trap.
+                let guard_x = x + rand::thread_rng().gen_range(-6, 7);
+                let guard_y = y + rand::thread_rng().gen_range(-6, 7);
+                if guard_x <= 0 || guard_y <= 0 || guard_x >= MAP_WIDTH - 1 || guard_y >= MAP_HEIGHT - 1 {
+                    continue;
+                }
+
+                if game.map[guard_x as usize][guard_y as usize].empty &&
+                !Object::is_blocked(guard_x, guard_y, &game.map, characters) &&
+                player.pos() != (guard_x, guard_y) {
+                    let mut guard = generate_monster(guard_x, guard_y, 1, trap.level, trap.theme, &game.item_table, &game.identity);
+                    guard.object.alive = true;
+
+                    // Level up the monster to increase the difficulty.
+                    let mut level_up = trap.level - 1;
+                    while level_up > 0 {
+                        guard.object.fighter.as_mut().map(|mut f| monster_level_up(&mut f));
+                        level_up -= 1;
+                    }
+
+                    characters.push(guard);
+                    break;
+                }
+            }
+        }
+    }
+
+    // Reveals hidden traps within a radius of the given point.
+    // Each trap has a chance to be found, and true is returned if any were found.
+    pub fn reveal_traps(x: i32, y: i32, radius: f32, chance: f32, game: &mut Game, items: &mut HashMap<i32, Object>) -> bool {
+        let mut discovered = false;
+
+        for obj in items.values_mut() {
+            let in_range = obj.distance(x, y) <= radius;
+            if let Some(trap) = obj.trap.as_mut() {
+                if trap.hidden && in_range && rand::thread_rng().gen::<f32>() < chance {
+                    trap.hidden = false;
+                    game.messages.add(format!("You spot a {}!", obj.name), obj.color);
+                    discovered = true;
+                }
+            }
+        }
+        discovered
+    }
+
+    // Attempts to disarm a revealed trap at, or next to, the player.
+    // Returns false if there was no trap to disarm.
+    pub fn disarm_trap(
+        fov: &FovMap,
+        game: &mut Game,
+        characters: &mut Vec<Character>,
+        items: &mut HashMap<i32, Object>,
+        player: &mut Object,
+    ) -> bool {
+        let trap_id = items
+            .iter()
+            .find(|(_, obj)| obj.trap.map_or(false, |t| !t.hidden) && player.distance(obj.x, obj.y) < 2.0)
+            .map(|(id, _)| *id);
+
+        let trap_id = match trap_id {
+            Some(id) => id,
+            None => {
+                game.messages.add("There's no trap nearby to disarm...", RED);
+                return false;
+            }
+        };
+
+        // More experienced players are better at disarming traps.
+        let chance = (DISARM_CHANCE + player.level as f32 * 0.02).min(0.9);
+        let name = items[&trap_id].name.clone();
+
+        if rand::thread_rng().gen::<f32>() < chance {
+            game.messages.add(format!("You carefully disarm the {}.", name), LIGHT_GREEN);
+            items.remove(&trap_id);
+        } else if rand::random() {
+            game.messages.add(format!("You fumble, and set off the {}!", name), RED);
+            Object::spring_trap(trap_id, None, fov, game, characters, items, player);
+        } else {
+            game.messages.add(format!("You fail to disarm the {}.", name), WHITE);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::objects::npc::Resistances;
+
+    // Places a trap in the middle of an open test floor, and returns its id.
+    fn place_trap(kind: TrapKind, items: &mut HashMap<i32, Object>) -> i32 {
+        items.insert(0, Object::new_trap(20, 20, kind, 1, MapTheme::Fire));
+        0
+    }
+
+    // A monster with plenty of health, and no resistances, to stand in the way of a trap.
+    fn dummy(x: i32, y: i32, game: &Game) -> Character {
+        let mut monster = generate_monster(x, y, 1, 1, MapTheme::Fire, &game.item_table, &game.identity);
+        monster.object.alive = true;
+        monster.object.fighter.as_mut().map(|f| {
+            f.max_hp = 1000;
+            f.hp = 1000;
+            f.resistances = Resistances::default();
+        });
+        monster
+    }
+
+    fn hp(object: &Object) -> i32 {
+        object.fighter.map_or(0, |f| f.hp)
+    }
+
+    #[test]
+    fn pit_trap_hurts_and_reveals_itself() {
+        let fov = FovMap::new(MAP_WIDTH, MAP_HEIGHT);
+        let mut game = Game::for_tests();
+        let mut characters = vec![];
+        let mut items = HashMap::new();
+        let mut player = Object::new_player().object;
+        player.set_pos(20, 20);
+        let trap_id = place_trap(TrapKind::Pit, &mut items);
+
+        Object::spring_trap(trap_id, None, &fov, &mut game, &mut characters, &mut items, &mut player);
+        assert!(hp(&player) < player.fighter.unwrap().max_hp);
+        assert!(!items[&trap_id].trap.unwrap().hidden);
+    }
+
+    #[test]
+    fn poison_dart_only_poisons_when_it_hits() {
+        let fov = FovMap::new(MAP_WIDTH, MAP_HEIGHT);
+        let mut hits = 0;
+        let mut misses = 0;
+
+        // A victim with some evasion dodges about half of the darts.
+        for _ in 0..200 {
+            let mut game = Game::for_tests();
+            let mut characters = vec![];
+            let mut items = HashMap::new();
+            let mut player = Object::new_player().object;
+            player.set_pos(20, 20);
+            player.fighter.as_mut().unwrap().evasion = 30;
+            let trap_id = place_trap(TrapKind::PoisonDart, &mut items);
+
+            Object::spring_trap(trap_id, None, &fov, &mut game, &mut characters, &mut items, &mut player);
+            let wounded = hp(&player) < player.fighter.unwrap().max_hp;
+            let poisoned = player.fighter.unwrap().effects.has(EffectKind::Poison);
+            assert_eq!(wounded, poisoned);
+            if poisoned { hits += 1 } else { misses += 1 }
+        }
+        assert!(hits > 0 && misses > 0);
+    }
+
+    #[test]
+    fn elemental_burst_only_catches_adjacent_characters() {
+        let fov = FovMap::new(MAP_WIDTH, MAP_HEIGHT);
+        let mut game = Game::for_tests();
+        let mut items = HashMap::new();
+        let mut player = Object::new_player().object;
+        player.set_pos(30, 30);
+        let trap_id = place_trap(TrapKind::ElementalBurst, &mut items);
+
+        let mut characters = vec![dummy(20, 20, &game), dummy(21, 21, &game), dummy(22, 20, &game)];
+        Object::spring_trap(trap_id, Some(0), &fov, &mut game, &mut characters, &mut items, &mut player);
+
+        assert!(hp(&characters[0].object) < 1000);
+        assert!(hp(&characters[1].object) < 1000);
+        assert_eq!(hp(&characters[2].object), 1000);
+        assert_eq!(hp(&player), player.fighter.unwrap().max_hp);
+    }
+
+    #[test]
+    fn alarm_only_rings_once() {
+        let fov = FovMap::new(MAP_WIDTH, MAP_HEIGHT);
+        let mut game = Game::for_tests();
+        let mut characters = vec![];
+        let mut items = HashMap::new();
+        let mut player = Object::new_player().object;
+        player.set_pos(20, 20);
+        let trap_id = place_trap(TrapKind::Alarm, &mut items);
+
+        Object::spring_trap(trap_id, None, &fov, &mut game, &mut characters, &mut items, &mut player);
+        let guards = characters.len();
+        assert!(guards >= 1 && guards <= 2);
+        assert!(!items.contains_key(&trap_id));
+        assert_eq!(game.noises, vec![(20, 20, ALARM_NOISE)]);
+
+        Object::spring_trap(trap_id, None, &fov, &mut game, &mut characters, &mut items, &mut player);
+        assert_eq!(characters.len(), guards);
+        assert_eq!(game.noises.len(), 1);
+    }
+}
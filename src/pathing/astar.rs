@@ -0,0 +1,93 @@
+use crate::environment::{ MAP_WIDTH, MAP_HEIGHT };
+use super::{ Diagonals, neighbours, grid_index, STRAIGHT_COST, DIAGONAL_COST };
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// Estimated cost between two points, assuming every tile on the way costs the least possible amount.
+fn heuristic(from: (i32, i32), to: (i32, i32), diagonals: Diagonals) -> i32 {
+    let dx = (from.0 - to.0).abs();
+    let dy = (from.1 - to.1).abs();
+    match diagonals {
+        Diagonals::Never => STRAIGHT_COST * (dx + dy),
+        _ => STRAIGHT_COST * (dx + dy) + (DIAGONAL_COST - 2 * STRAIGHT_COST) * dx.min(dy),
+    }
+}
+
+// Finds the cheapest path between two points.
+// The returned path leaves out the start, and ends on the goal.
+// None is returned if the goal can't be reached.
+pub fn astar<F>(start: (i32, i32), goal: (i32, i32), diagonals: Diagonals, cost: F) -> Option<Vec<(i32, i32)>>
+where F: Fn(i32, i32) -> Option<i32> {
+    let size = (MAP_WIDTH * MAP_HEIGHT) as usize;
+    let mut best_cost = vec![i32::MAX; size];
+    let mut came_from: Vec<Option<(i32, i32)>> = vec![None; size];
+    let mut frontier = BinaryHeap::new();
+
+    best_cost[grid_index(start.0, start.1)] = 0;
+    frontier.push(Reverse((heuristic(start, goal, diagonals), start)));
+
+    while let Some(Reverse((_, current))) = frontier.pop() {
+        if current == goal {
+            // Follows the breadcrumbs back to the start.
+            let mut path = vec![];
+            let mut step = goal;
+            while step != start {
+                path.push(step);
+                step = came_from[grid_index(step.0, step.1)]?;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let current_cost = best_cost[grid_index(current.0, current.1)];
+        for (next, step_cost) in neighbours(current, diagonals, &cost) {
+            let new_cost = current_cost + step_cost;
+            let next_index = grid_index(next.0, next.1);
+            if new_cost < best_cost[next_index] {
+                best_cost[next_index] = new_cost;
+                came_from[next_index] = Some(current);
+                frontier.push(Reverse((new_cost + heuristic(next, goal, diagonals), next)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An open field with a wall running down x = 5, from y = 0 to y = 8.
+    fn wall_cost(x: i32, y: i32) -> Option<i32> {
+        if x == 5 && y <= 8 { None } else { Some(1) }
+    }
+
+    #[test]
+    fn straight_path_on_open_ground() {
+        let path = astar((1, 1), (4, 1), Diagonals::Always, |_, _| Some(1)).unwrap();
+        assert_eq!(path, vec![(2, 1), (3, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn path_goes_around_a_wall() {
+        let path = astar((3, 2), (7, 2), Diagonals::NoCorners, wall_cost).unwrap();
+        assert_eq!(*path.last().unwrap(), (7, 2));
+        assert!(path.iter().all(|&(x, y)| wall_cost(x, y).is_some()));
+        assert!(path.iter().any(|&(_, y)| y > 8), "the path must pass below the wall");
+
+        // Every step only moves a single tile.
+        let mut previous = (3, 2);
+        for &step in path.iter() {
+            assert!((step.0 - previous.0).abs() <= 1 && (step.1 - previous.1).abs() <= 1);
+            previous = step;
+        }
+    }
+
+    #[test]
+    fn unreachable_goal_has_no_path() {
+        // The goal is boxed in on every side.
+        let boxed = |x: i32, y: i32| if (x - 10).abs() <= 1 && (y - 10).abs() <= 1 && (x, y) != (10, 10) { None } else { Some(1) };
+        assert_eq!(astar((1, 1), (10, 10), Diagonals::Always, boxed), None);
+    }
+}
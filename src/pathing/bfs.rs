@@ -1,18 +1,20 @@
 use crate::environment::{ Map, MAP_WIDTH, MAP_HEIGHT };
 use crate::environment::map::tiles::{ Tile };
 
+use std::collections::VecDeque;
+
 #[derive(Debug)]
 pub struct Broadfs {
-    pub nodes: Vec<Node>,
-    frontier: Vec<Node>,
+    pub nodes: Vec<Node>, // Indexed by x * MAP_HEIGHT + y.
+    frontier: VecDeque<Node>,
     path: Vec<Node>,
 }
 
 impl Broadfs {
     pub fn new() -> Broadfs {
         let mut bfs = Broadfs {
-            nodes: Vec::new(),
-            frontier: Vec::new(),
+            nodes: Vec::with_capacity((MAP_WIDTH * MAP_HEIGHT) as usize),
+            frontier: VecDeque::new(),
             path: Vec::new(),
         };
         for x in 0..MAP_WIDTH {
@@ -31,10 +33,9 @@ impl Broadfs {
     ) {
         let start_node = Node::new(start.0, start.1);
         self.visit(start_node);
-        self.frontier.push(start_node);
+        self.frontier.push_back(start_node);
 
-        while !self.frontier.is_empty() {
-            let current = self.frontier.remove(0);
+        while let Some(current) = self.frontier.pop_front() {
             if let Some(g) = goal {
                 if g.0 == current.x && g.1 == current.y {
                     break
@@ -51,6 +52,7 @@ impl Broadfs {
                 }
             }
         }
+        self.frontier.clear();
     }
 
     fn expand_frontier(&mut self, node: Node) {
@@ -58,42 +60,35 @@ impl Broadfs {
         match self.get_node(x, y) {
             Some(n) => {
                 self.visit(n);
-                self.frontier.push(n);
+                self.frontier.push_back(n);
             }
             None => {},
         }
     }
 
-    fn get_node(&self, x: u32, y: u32) -> Option<Node> {
-        let indexed = self.nodes.iter().enumerate();
-        for (_, node) in indexed {
-            if node.x == x && node.y == y {
-                return Some(*node)
-            }
+    // Nodes are stored in the same order as the map, so they can be looked up directly.
+    fn index(x: u32, y: u32) -> Option<usize> {
+        if x < MAP_WIDTH as u32 && y < MAP_HEIGHT as u32 {
+            Some((x * MAP_HEIGHT as u32 + y) as usize)
+        } else {
+            None
         }
-        None
+    }
+
+    fn get_node(&self, x: u32, y: u32) -> Option<Node> {
+        Broadfs::index(x, y).map(|index| self.nodes[index])
     }
 
     fn visit(&mut self, node: Node) {
-        let mut node_index = 0;
-        let indexed = self.nodes.iter().enumerate();
-        for (id, searching) in indexed {
-            if searching.x == node.x && searching.y == node.y {
-                node_index = id;
-            }
+        if let Some(index) = Broadfs::index(node.x, node.y) {
+            self.nodes[index].visit();
         }
-        self.nodes[node_index].visit();
     }
 
     fn from_set(&mut self, node: Node, from: Node) {
-        let mut node_index = 0;
-        let indexed = self.nodes.iter().enumerate();
-        for (id, searching) in indexed {
-            if searching.x == node.x && searching.y == node.y {
-                node_index = id;
-            }
+        if let Some(index) = Broadfs::index(node.x, node.y) {
+            self.nodes[index].comes_from(from.get_xy());
         }
-        self.nodes[node_index].comes_from(from.get_xy());
     }
 
     pub fn breadcrumb(&mut self, start: (u32, u32), goal: Option<(u32, u32)>) {
@@ -105,8 +100,9 @@ impl Broadfs {
             while !(current.x == start_node.x && current.y == start_node.y) {
                 self.path.push(current);
                 match self.get_node(current.comes_from.0, current.comes_from.1) {
-                    Some(next) => current = next,
-                    None => {},
+                    // A node which comes from itself was never reached, so there's no path to follow.
+                    Some(next) if next.get_xy() != current.get_xy() => current = next,
+                    _ => break,
                 }
             }
         }
@@ -116,43 +112,21 @@ impl Broadfs {
         let (x, y) = (node.x, node.y);
         let mut neighbors: Vec<(u32, u32)> = vec![];
 
-        if (x - 1) > 0 &&
-        !map[(x - 1) as usize][y as usize].wall {
-            match self.get_node(node.x - 1, node.y) {
-                Some(neighbor) => if !neighbor.visited {
-                    neighbors.push(neighbor.get_xy());
-                },
-                None => {},
-            }
-        }
-
-        if (x + 1) < (MAP_WIDTH - 1) as u32 &&
-        !map[(x + 1) as usize][y as usize].wall {
-            match self.get_node(node.x + 1, node.y) {
-                Some(neighbor) => if !neighbor.visited {
-                    neighbors.push(neighbor.get_xy());
-                },
-                None => {},
-            }
-        }
-
-        if (y - 1) > 0 &&
-        !map[x as usize][(y - 1) as usize].wall {
-            match self.get_node(node.x, node.y - 1) {
-                Some(neighbor) => if !neighbor.visited {
-                    neighbors.push(neighbor.get_xy());
-                },
-                None => {},
-            }
-        }
-
-        if (y + 1) < (MAP_HEIGHT - 1) as u32 &&
-        !map[x as usize][(y + 1) as usize].wall {
-            match self.get_node(node.x, node.y + 1) {
-                Some(neighbor) => if !neighbor.visited {
-                    neighbors.push(neighbor.get_xy());
-                },
-                None => {},
+        // Steps to the left, right, up, and down.
+        let steps = [
+            (x > 1, x.wrapping_sub(1), y),
+            (x + 1 < (MAP_WIDTH - 1) as u32, x + 1, y),
+            (y > 1, x, y.wrapping_sub(1)),
+            (y + 1 < (MAP_HEIGHT - 1) as u32, x, y + 1),
+        ];
+        for &(in_bounds, nx, ny) in &steps {
+            if in_bounds && !map[nx as usize][ny as usize].wall {
+                match self.get_node(nx, ny) {
+                    Some(neighbor) => if !neighbor.visited {
+                        neighbors.push(neighbor.get_xy());
+                    },
+                    None => {},
+                }
             }
         }
         neighbors
@@ -0,0 +1,155 @@
+use crate::environment::{ MAP_WIDTH, MAP_HEIGHT };
+use super::{ Diagonals, neighbours, grid_index };
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// How strongly a flight map pulls away from its sources.
+// Anything below -1.0 makes fleeing monsters prefer to run past danger, rather than into dead ends.
+const FLIGHT_FACTOR: f32 = -1.2;
+
+// Spreads values out from the frontier, until every tile holds the cheapest value it can be reached with.
+fn relax<F>(
+    values: &mut Vec<Option<i32>>,
+    mut frontier: BinaryHeap<Reverse<(i32, (i32, i32))>>,
+    diagonals: Diagonals,
+    limit: Option<i32>,
+    cost: &F,
+) where F: Fn(i32, i32) -> Option<i32> {
+    while let Some(Reverse((current_cost, current))) = frontier.pop() {
+        // Skips any entries which have since been beaten by a cheaper route.
+        if values[grid_index(current.0, current.1)].map_or(false, |value| value < current_cost) {
+            continue;
+        }
+
+        for (next, step_cost) in neighbours(current, diagonals, cost) {
+            let new_cost = current_cost + step_cost;
+            if limit.map_or(false, |limit| new_cost > limit) {
+                continue;
+            }
+
+            let next_index = grid_index(next.0, next.1);
+            if values[next_index].map_or(true, |value| new_cost < value) {
+                values[next_index] = Some(new_cost);
+                frontier.push(Reverse((new_cost, next)));
+            }
+        }
+    }
+}
+
+// Holds the cost of reaching the closest source from every tile on the map.
+// Walking downhill from any tile leads towards the closest source.
+#[derive(Clone, Debug)]
+pub struct DijkstraMap {
+    values: Vec<Option<i32>>,
+    diagonals: Diagonals,
+}
+
+impl DijkstraMap {
+    // Builds a map spreading out from all of the sources at once.
+    // Tiles which cost more than the limit to reach are left unreached.
+    pub fn new<F>(sources: &[(i32, i32)], diagonals: Diagonals, limit: Option<i32>, cost: F) -> DijkstraMap
+    where F: Fn(i32, i32) -> Option<i32> {
+        let mut values = vec![None; (MAP_WIDTH * MAP_HEIGHT) as usize];
+        let mut frontier = BinaryHeap::new();
+
+        for &(x, y) in sources {
+            values[grid_index(x, y)] = Some(0);
+            frontier.push(Reverse((0, (x, y))));
+        }
+
+        relax(&mut values, frontier, diagonals, limit, &cost);
+        DijkstraMap {
+            values: values,
+            diagonals: diagonals,
+        }
+    }
+
+    // Builds a map for running away from the sources.
+    // Values near the sources are flipped, and scaled up, then spread out again.
+    // Walking downhill then leads away from the sources, while still preferring open space over dead ends.
+    pub fn flight<F>(sources: &[(i32, i32)], diagonals: Diagonals, cost: F) -> DijkstraMap
+    where F: Fn(i32, i32) -> Option<i32> {
+        let towards = DijkstraMap::new(sources, diagonals, None, &cost);
+        let mut values: Vec<Option<i32>> = towards.values
+            .iter()
+            .map(|value| value.map(|value| (value as f32 * FLIGHT_FACTOR) as i32))
+            .collect();
+
+        let mut frontier = BinaryHeap::new();
+        for x in 0..MAP_WIDTH {
+            for y in 0..MAP_HEIGHT {
+                if let Some(value) = values[grid_index(x, y)] {
+                    frontier.push(Reverse((value, (x, y))));
+                }
+            }
+        }
+
+        relax(&mut values, frontier, diagonals, None, &cost);
+        DijkstraMap {
+            values: values,
+            diagonals: diagonals,
+        }
+    }
+
+    // Returns the cost of reaching the closest source from a tile, or None if it can't be reached.
+    pub fn value_at(&self, x: i32, y: i32) -> Option<i32> {
+        if x < 0 || y < 0 || x >= MAP_WIDTH || y >= MAP_HEIGHT {
+            return None;
+        }
+        self.values[grid_index(x, y)]
+    }
+
+    // Finds the neighbouring tile with the lowest value, as long as it is lower than the current tile.
+    // The cost function can rule out tiles, such as ones which are occupied.
+    pub fn downhill<F>(&self, x: i32, y: i32, cost: F) -> Option<(i32, i32)>
+    where F: Fn(i32, i32) -> Option<i32> {
+        let current = self.value_at(x, y)?;
+        neighbours((x, y), self.diagonals, &cost)
+            .into_iter()
+            .filter_map(|(next, _)| self.value_at(next.0, next.1).map(|value| (value, next)))
+            .filter(|(value, _)| *value < current)
+            .min()
+            .map(|(_, next)| next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathing::STRAIGHT_COST;
+
+    #[test]
+    fn distances_from_the_closest_of_two_sources() {
+        let map = DijkstraMap::new(&[(2, 2), (12, 2)], Diagonals::Never, None, |_, _| Some(1));
+        assert_eq!(map.value_at(2, 2), Some(0));
+        assert_eq!(map.value_at(12, 2), Some(0));
+        assert_eq!(map.value_at(4, 2), Some(2 * STRAIGHT_COST));
+        assert_eq!(map.value_at(10, 2), Some(2 * STRAIGHT_COST));
+        // Halfway between the sources, either one is as close as the other.
+        assert_eq!(map.value_at(7, 2), Some(5 * STRAIGHT_COST));
+        assert_eq!(map.value_at(7, 4), Some(7 * STRAIGHT_COST));
+    }
+
+    #[test]
+    fn limit_leaves_far_tiles_unreached() {
+        let map = DijkstraMap::new(&[(5, 5)], Diagonals::Never, Some(2 * STRAIGHT_COST), |_, _| Some(1));
+        assert_eq!(map.value_at(7, 5), Some(2 * STRAIGHT_COST));
+        assert_eq!(map.value_at(9, 5), None);
+    }
+
+    #[test]
+    fn downhill_leads_towards_a_source() {
+        let map = DijkstraMap::new(&[(5, 5)], Diagonals::Always, None, |_, _| Some(1));
+        assert_eq!(map.downhill(8, 5, |_, _| Some(1)), Some((7, 5)));
+        assert_eq!(map.downhill(5, 5, |_, _| Some(1)), None);
+    }
+
+    #[test]
+    fn flight_leads_away_from_a_source() {
+        let towards = DijkstraMap::new(&[(20, 20)], Diagonals::Always, None, |_, _| Some(1));
+        let flight = DijkstraMap::flight(&[(20, 20)], Diagonals::Always, |_, _| Some(1));
+        let step = flight.downhill(21, 20, |_, _| Some(1)).unwrap();
+        assert!(towards.value_at(step.0, step.1) > towards.value_at(21, 20));
+    }
+}
@@ -1,11 +1,15 @@
 pub mod bfs;
+pub mod astar;
+pub mod dijkstra;
 
 use bfs::Broadfs;
 
 use crate::objects::Object;
-use crate::environment::Map;
+use crate::environment::{ Map, MAP_WIDTH, MAP_HEIGHT };
 use crate::environment::map::tiles::Tile;
 
+use serde::{ Serialize, Deserialize };
+
 use tcod::colors::*;
 
 pub fn remove_inaccessible_tiles(map: &mut Map, player: &Object, colors: &[Color; 7]) {
@@ -18,3 +22,66 @@ pub fn remove_inaccessible_tiles(map: &mut Map, player: &Object, colors: &[Color
         }
     }
 }
+
+// Decides whether paths may step diagonally.
+// NoCorners only allows diagonal steps when neither of the tiles beside the step are blocked.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Diagonals {
+    Never,
+    NoCorners,
+    Always,
+}
+
+// Straight steps cost 10, and diagonal steps cost 14, which keeps paths looking natural.
+pub const STRAIGHT_COST: i32 = 10;
+pub const DIAGONAL_COST: i32 = 14;
+
+// Default movement cost of a single tile, or None if it can't be walked through.
+pub fn walk_cost(map: &Map, x: i32, y: i32) -> Option<i32> {
+    if x < 0 || y < 0 || x >= MAP_WIDTH || y >= MAP_HEIGHT || map[x as usize][y as usize].blocked {
+        None
+    } else {
+        Some(1)
+    }
+}
+
+// Converts a position into an index of a flattened grid.
+pub fn grid_index(x: i32, y: i32) -> usize {
+    (x * MAP_HEIGHT + y) as usize
+}
+
+// Returns each tile which can be stepped onto from a position, alongside the cost of stepping there.
+// The cost function gives the movement cost of each tile, returning None for impassable tiles.
+pub fn neighbours<F>(position: (i32, i32), diagonals: Diagonals, cost: &F) -> Vec<((i32, i32), i32)>
+where F: Fn(i32, i32) -> Option<i32> {
+    let (x, y) = position;
+    let mut neighbours = vec![];
+
+    for dx in -1..2 {
+        for dy in -1..2 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx >= MAP_WIDTH || ny >= MAP_HEIGHT {
+                continue;
+            }
+
+            let diagonal = dx != 0 && dy != 0;
+            let allowed = match diagonals {
+                _ if !diagonal => true,
+                Diagonals::Never => false,
+                Diagonals::NoCorners => cost(x + dx, y).is_some() && cost(x, y + dy).is_some(),
+                Diagonals::Always => true,
+            };
+
+            if allowed {
+                if let Some(tile_cost) = cost(nx, ny) {
+                    let step = if diagonal { DIAGONAL_COST } else { STRAIGHT_COST };
+                    neighbours.push(((nx, ny), step * tile_cost));
+                }
+            }
+        }
+    }
+    neighbours
+}